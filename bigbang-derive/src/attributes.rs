@@ -0,0 +1,276 @@
+use super::rename::RenameRule;
+use bigbang_uri_template::UriTemplate;
+use syn::{
+    ext::IdentExt, spanned::Spanned, Attribute, DeriveInput, Field, Ident, Lit, LitStr, Meta,
+    NestedMeta, Path, Type,
+};
+
+/// A single link declared on the type.
+pub struct Link {
+    /// The relation of the link.
+    pub rel: LitStr,
+    /// The URI Template of the link.
+    pub href: LitStr,
+    /// Whether the link is left as a template for the client to expand.
+    pub templated: bool,
+}
+
+/// The attributes declared on the type itself.
+pub struct Container {
+    /// The path to the module that `HalDocument` is in.
+    pub krate: Path,
+    /// The links to add to the document.
+    pub links: Vec<Link>,
+    /// The rule that serde uses to rename the fields of the type.
+    pub rename_all: Option<RenameRule>,
+}
+
+/// How an embedded field holds the resources to embed.
+pub enum EmbeddedKind {
+    /// The field holds a single resource.
+    Single,
+    /// The field holds a list of resources, which are always embedded as an array.
+    List,
+    /// The field holds a resource that might not be present.
+    Optional,
+}
+
+/// A single field that is to be embedded in the document.
+pub struct Embedded {
+    /// The field holding the resources.
+    pub field: Ident,
+    /// The name of the field in the serialized data.
+    pub key: String,
+    /// The relation to embed the resources under.
+    pub rel: String,
+    /// How the field holds the resources.
+    pub kind: EmbeddedKind,
+}
+
+impl Container {
+    /// Parse the attributes declared on the type.
+    pub fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut krate = None;
+        let mut links = vec![];
+
+        for meta in nested_meta(&input.attrs, "hal")? {
+            match meta {
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("crate") => {
+                    krate = Some(string_literal(&value.lit)?.parse::<Path>()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("self_link") => {
+                    links.insert(
+                        0,
+                        Link {
+                            rel: LitStr::new("self", value.span()),
+                            href: template_literal(&value.lit)?,
+                            templated: false,
+                        },
+                    );
+                }
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("link") => {
+                    let mut rel = None;
+                    let mut href = None;
+                    let mut templated = false;
+
+                    for meta in &list.nested {
+                        match meta {
+                            NestedMeta::Meta(Meta::NameValue(value))
+                                if value.path.is_ident("rel") =>
+                            {
+                                rel = Some(string_literal(&value.lit)?);
+                            }
+                            NestedMeta::Meta(Meta::NameValue(value))
+                                if value.path.is_ident("href") =>
+                            {
+                                href = Some(template_literal(&value.lit)?);
+                            }
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("templated") => {
+                                templated = true;
+                            }
+                            other => {
+                                return Err(syn::Error::new_spanned(
+                                    other,
+                                    "expected `rel = \"...\"`, `href = \"...\"` or `templated`",
+                                ))
+                            }
+                        }
+                    }
+
+                    links.push(Link {
+                        rel: rel.ok_or_else(|| {
+                            syn::Error::new_spanned(&list, "link is missing `rel = \"...\"`")
+                        })?,
+                        href: href.ok_or_else(|| {
+                            syn::Error::new_spanned(&list, "link is missing `href = \"...\"`")
+                        })?,
+                        templated,
+                    });
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "expected `self_link = \"...\"`, `link(...)` or `crate = \"...\"`",
+                    ))
+                }
+            }
+        }
+
+        let mut rename_all = None;
+        for meta in serde_meta(&input.attrs) {
+            if let NestedMeta::Meta(Meta::NameValue(value)) = meta {
+                if value.path.is_ident("rename_all") {
+                    let rule = string_literal(&value.lit)?;
+                    rename_all = Some(
+                        RenameRule::parse(&rule.value())
+                            .ok_or_else(|| syn::Error::new_spanned(rule, "unknown rename rule"))?,
+                    );
+                }
+            }
+        }
+
+        Ok(Self {
+            krate: krate.unwrap_or_else(|| syn::parse_quote!(crate::http::hal)),
+            links,
+            rename_all,
+        })
+    }
+}
+
+impl Embedded {
+    /// Parse the attributes declared on a single field.
+    ///
+    /// # Returns
+    /// The details of how to embed the field, or `None` if the field isn't embedded
+    pub fn parse(field: &Field, container: &Container) -> syn::Result<Option<Self>> {
+        let ident = field.ident.clone().unwrap();
+
+        let mut rel = None;
+        let mut embedded = false;
+        for meta in nested_meta(&field.attrs, "hal")? {
+            match meta {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("embedded") => {
+                    embedded = true;
+                }
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("embedded") => {
+                    embedded = true;
+                    for meta in &list.nested {
+                        match meta {
+                            NestedMeta::Meta(Meta::NameValue(value))
+                                if value.path.is_ident("rel") =>
+                            {
+                                rel = Some(string_literal(&value.lit)?.value());
+                            }
+                            other => {
+                                return Err(syn::Error::new_spanned(
+                                    other,
+                                    "expected `rel = \"...\"`",
+                                ))
+                            }
+                        }
+                    }
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "expected `embedded` or `embedded(rel = \"...\")`",
+                    ))
+                }
+            }
+        }
+
+        if !embedded {
+            return Ok(None);
+        }
+
+        let mut key = None;
+        for meta in serde_meta(&field.attrs) {
+            if let NestedMeta::Meta(Meta::NameValue(value)) = meta {
+                if value.path.is_ident("rename") {
+                    key = Some(string_literal(&value.lit)?.value());
+                }
+            }
+        }
+        let key = key.unwrap_or_else(|| {
+            let name = ident.unraw().to_string();
+            match &container.rename_all {
+                Some(rule) => rule.apply(&name),
+                None => name,
+            }
+        });
+
+        Ok(Some(Self {
+            rel: rel.unwrap_or_else(|| key.clone()),
+            key,
+            kind: EmbeddedKind::of(&field.ty),
+            field: ident,
+        }))
+    }
+}
+
+impl EmbeddedKind {
+    /// Work out how a field of the given type holds the resources to embed.
+    fn of(ty: &Type) -> Self {
+        let name = match ty {
+            Type::Path(path) => path.path.segments.last().map(|segment| &segment.ident),
+            _ => None,
+        };
+
+        match name {
+            Some(name) if name == "Vec" => Self::List,
+            Some(name) if name == "Option" => Self::Optional,
+            _ => Self::Single,
+        }
+    }
+}
+
+/// Get all of the items from the attributes with the given name.
+fn nested_meta(attrs: &[Attribute], name: &str) -> syn::Result<Vec<NestedMeta>> {
+    let mut result = vec![];
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident(name)) {
+        match attr.parse_meta()? {
+            Meta::List(list) => result.extend(list.nested),
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    format!("expected #[{name}(...)]"),
+                ))
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Get all of the items from the serde attributes that we are able to understand.
+/// Anything that doesn't parse is left for serde itself to complain about.
+fn serde_meta(attrs: &[Attribute]) -> Vec<NestedMeta> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Get the value of a literal that must be a string.
+fn string_literal(lit: &Lit) -> syn::Result<LitStr> {
+    match lit {
+        Lit::Str(lit) => Ok(lit.clone()),
+        other => Err(syn::Error::new_spanned(other, "expected a string")),
+    }
+}
+
+/// Get the value of a literal that must be a string containing a valid URI Template.
+fn template_literal(lit: &Lit) -> syn::Result<LitStr> {
+    let lit = string_literal(lit)?;
+
+    UriTemplate::parse(lit.value())
+        .map_err(|e| syn::Error::new_spanned(&lit, format!("invalid URI Template: {e}")))?;
+
+    Ok(lit)
+}
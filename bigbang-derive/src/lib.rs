@@ -0,0 +1,240 @@
+#![deny(clippy::all, clippy::pedantic)]
+
+mod attributes;
+mod rename;
+
+use attributes::{Container, Embedded, EmbeddedKind, Link};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// Derive a conversion from a type into a `HalDocument`.
+///
+/// The type must be a struct with named fields that implements `Serialize`. The serialized form of
+/// the struct is used as the data of the document, and the top-level values in it are used to
+/// expand the URI Templates of any links.
+///
+/// # Container attributes
+/// - `#[hal(self_link = "/people/{id}")]` - The template of the `self` link
+/// - `#[hal(link(rel = "friends", href = "/people/{id}/friends"))]` - The template of a related link
+/// - `#[hal(link(rel = "search", href = "/people{?name}", templated))]` - A link that is left as a
+///   template for the client to expand
+/// - `#[hal(crate = "my_crate::hal")]` - The path to the module that `HalDocument` is in. Defaults
+///   to `crate::http::hal`, which is where the service has it
+///
+/// # Field attributes
+/// - `#[hal(embedded)]` - Embed the field, using its serialized name as the relation
+/// - `#[hal(embedded(rel = "pets"))]` - Embed the field under the given relation
+///
+/// Embedded fields are removed from the data of the document. They must be convertible into
+/// `HalDocument` themselves, or be a `Vec` or `Option` of something that is.
+///
+/// The generated code refers to `serde_json` and `bigbang_uri_template` by name, so the crate using
+/// the derive must depend on both.
+#[proc_macro_derive(HalResource, attributes(hal))]
+pub fn derive_hal_resource(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Generate the conversion for the type.
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "HalResource can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "HalResource can only be derived for structs",
+            ))
+        }
+    };
+
+    let container = Container::parse(input)?;
+    let embedded = fields
+        .iter()
+        .filter_map(|field| Embedded::parse(field, &container).transpose())
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let krate = &container.krate;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let remove_embedded = if embedded.is_empty() {
+        quote! {}
+    } else {
+        let keys = embedded.iter().map(|embedded| &embedded.key);
+        quote! {
+            if let ::serde_json::Value::Object(data) = &mut data {
+                #( data.remove(#keys); )*
+            }
+        }
+    };
+
+    let values = if container.links.iter().any(|link| !link.templated) {
+        quote! {
+            let values = ::bigbang_uri_template::TemplateValues::from(&data);
+        }
+    } else {
+        quote! {}
+    };
+
+    let links = container.links.iter().map(expand_link);
+    let embedded = embedded.iter().map(expand_embedded);
+
+    Ok(quote! {
+        impl #impl_generics ::std::convert::From<#name #ty_generics> for #krate::HalDocument #where_clause {
+            fn from(resource: #name #ty_generics) -> Self {
+                #[allow(unused_mut)]
+                let mut data = ::serde_json::to_value(&resource).unwrap();
+                #remove_embedded
+                #values
+
+                let document = #krate::HalDocument::new(data);
+                #( let document = #links; )*
+                #( let document = #embedded; )*
+
+                document
+            }
+        }
+    })
+}
+
+/// Generate the code to add a single link to the document.
+fn expand_link(link: &Link) -> TokenStream2 {
+    let Link {
+        rel,
+        href,
+        templated,
+    } = link;
+    let message = format!(
+        "Invalid URI Template for link {}: {}",
+        rel.value(),
+        href.value()
+    );
+
+    // The template has already been validated with the same parser, so this can't fail.
+    let template = quote! {
+        ::bigbang_uri_template::UriTemplate::parse(#href).expect(#message)
+    };
+
+    if *templated {
+        quote! { document.with_link(#rel, &#template) }
+    } else {
+        quote! { document.with_link(#rel, #template.expand(&values)) }
+    }
+}
+
+/// Generate the code to embed a single field in the document.
+fn expand_embedded(embedded: &Embedded) -> TokenStream2 {
+    let Embedded {
+        field, rel, kind, ..
+    } = embedded;
+
+    match kind {
+        EmbeddedKind::Single => quote! {
+            document.with_embedded(#rel, resource.#field.into())
+        },
+        EmbeddedKind::List => quote! {
+            document.with_embedded_list(#rel, resource.#field.into_iter().map(::std::convert::Into::into))
+        },
+        EmbeddedKind::Optional => quote! {
+            match resource.#field {
+                ::std::option::Option::Some(embedded) => document.with_embedded(#rel, embedded.into()),
+                ::std::option::Option::None => document,
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn error(input: &DeriveInput) -> String {
+        match expand(input) {
+            Ok(_) => panic!("Expected the derive to fail"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn invalid_self_link() {
+        let input = parse_quote! {
+            #[hal(self_link = "/people/{id")]
+            struct Person {
+                id: String,
+            }
+        };
+
+        assert_eq!(
+            error(&input),
+            "invalid URI Template: Unclosed expression starting at position 8"
+        );
+    }
+
+    #[test]
+    fn invalid_link() {
+        let input = parse_quote! {
+            #[hal(link(rel = "friends", href = "/people/{person id}/friends"))]
+            struct Person {
+                id: String,
+            }
+        };
+
+        assert_eq!(
+            error(&input),
+            "invalid URI Template: Invalid variable: person id"
+        );
+    }
+
+    #[test]
+    fn valid_links() {
+        let input = parse_quote! {
+            #[hal(self_link = "/people/{id}", link(rel = "search", href = "/people{?name}", templated))]
+            struct Person {
+                id: String,
+            }
+        };
+
+        assert!(expand(&input).is_ok());
+    }
+
+    #[test]
+    fn crate_path() {
+        let input = parse_quote! {
+            #[hal(crate = "other::hal")]
+            struct Person {
+                id: String,
+            }
+        };
+
+        let expanded = expand(&input).unwrap().to_string();
+        assert!(expanded.contains("for other :: hal :: HalDocument"));
+        assert!(!expanded.contains("crate :: http"));
+    }
+
+    #[test]
+    fn invalid_crate_path() {
+        let input = parse_quote! {
+            #[hal(crate = "other hal")]
+            struct Person {
+                id: String,
+            }
+        };
+
+        assert_eq!(error(&input), "unexpected token");
+    }
+}
@@ -0,0 +1,139 @@
+/// The rules that serde supports for renaming fields, as used by `#[serde(rename_all = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum RenameRule {
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    /// Parse the name of a rename rule.
+    ///
+    /// # Returns
+    /// The rule, or `None` if serde doesn't support it
+    pub fn parse(rule: &str) -> Option<Self> {
+        match rule {
+            "lowercase" => Some(Self::LowerCase),
+            "UPPERCASE" => Some(Self::UpperCase),
+            "PascalCase" => Some(Self::PascalCase),
+            "camelCase" => Some(Self::CamelCase),
+            "snake_case" => Some(Self::SnakeCase),
+            "SCREAMING_SNAKE_CASE" => Some(Self::ScreamingSnakeCase),
+            "kebab-case" => Some(Self::KebabCase),
+            "SCREAMING-KEBAB-CASE" => Some(Self::ScreamingKebabCase),
+            _ => None,
+        }
+    }
+
+    /// Apply the rule to the name of a field, which is expected to be in snake case.
+    pub fn apply(self, field: &str) -> String {
+        match self {
+            Self::LowerCase | Self::SnakeCase => field.to_owned(),
+            Self::UpperCase | Self::ScreamingSnakeCase => field.to_ascii_uppercase(),
+            Self::PascalCase => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+            Self::CamelCase => {
+                let pascal = Self::PascalCase.apply(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            Self::KebabCase => field.replace('_', "-"),
+            Self::ScreamingKebabCase => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_rules() {
+        let cases = [
+            (RenameRule::LowerCase, "trace_id"),
+            (RenameRule::UpperCase, "TRACE_ID"),
+            (RenameRule::PascalCase, "TraceId"),
+            (RenameRule::CamelCase, "traceId"),
+            (RenameRule::SnakeCase, "trace_id"),
+            (RenameRule::ScreamingSnakeCase, "TRACE_ID"),
+            (RenameRule::KebabCase, "trace-id"),
+            (RenameRule::ScreamingKebabCase, "TRACE-ID"),
+        ];
+
+        for (rule, expected) in &cases {
+            assert_eq!(rule.apply("trace_id"), *expected, "{rule:?}");
+        }
+    }
+
+    /// Field names covering the cases that the rules have to handle.
+    const FIELDS: [&str; 5] = ["id", "trace_id", "http_status_code", "value2", "a_b"];
+
+    /// Serialize a struct with the given rename rule, and get the names that serde gives the
+    /// fields.
+    macro_rules! serde_names {
+        ($rule:literal) => {{
+            #[derive(serde::Serialize, Default)]
+            #[serde(rename_all = $rule)]
+            #[allow(dead_code)]
+            struct Fields {
+                id: u8,
+                trace_id: u8,
+                http_status_code: u8,
+                value2: u8,
+                a_b: u8,
+            }
+
+            match serde_json::to_value(Fields::default()).unwrap() {
+                serde_json::Value::Object(fields) => fields.keys().cloned().collect::<Vec<_>>(),
+                _ => unreachable!(),
+            }
+        }};
+    }
+
+    #[test]
+    fn matches_serde() {
+        let cases = [
+            ("lowercase", serde_names!("lowercase")),
+            ("UPPERCASE", serde_names!("UPPERCASE")),
+            ("PascalCase", serde_names!("PascalCase")),
+            ("camelCase", serde_names!("camelCase")),
+            ("snake_case", serde_names!("snake_case")),
+            ("SCREAMING_SNAKE_CASE", serde_names!("SCREAMING_SNAKE_CASE")),
+            ("kebab-case", serde_names!("kebab-case")),
+            ("SCREAMING-KEBAB-CASE", serde_names!("SCREAMING-KEBAB-CASE")),
+        ];
+
+        for (name, expected) in &cases {
+            let rule = RenameRule::parse(name).unwrap();
+            let actual = FIELDS
+                .iter()
+                .map(|field| rule.apply(field))
+                .collect::<Vec<_>>();
+
+            assert_eq!(&actual, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn parse_rules() {
+        assert_eq!(RenameRule::parse("camelCase"), Some(RenameRule::CamelCase));
+        assert_eq!(RenameRule::parse("Title Case"), None);
+    }
+}
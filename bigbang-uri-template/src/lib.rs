@@ -0,0 +1,684 @@
+#![deny(clippy::all, clippy::pedantic)]
+
+//! Parsing and expansion of URI Templates, as defined by RFC 6570.
+//! This is shared between the service, which expands the templates at runtime, and the derive
+//! macros, which check them at compile time, so that both always agree on what is valid.
+
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// A URI Template, as defined by RFC 6570.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UriTemplate {
+    template: String,
+    parts: Vec<Part>,
+}
+
+/// A single part of a URI Template.
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    /// Literal text that is copied as-is into the expansion.
+    Literal(String),
+    /// An expression that is replaced by the expansion of some variables.
+    Expression(Operator, Vec<VarSpec>),
+}
+
+/// The operator that controls how an expression is expanded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Simple,
+    Reserved,
+    Fragment,
+    Label,
+    Path,
+    PathParameter,
+    Query,
+    QueryContinuation,
+}
+
+/// A single variable within an expression.
+#[derive(Debug, Clone, PartialEq)]
+struct VarSpec {
+    name: String,
+    modifier: Modifier,
+}
+
+/// The modifier applied to a single variable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Modifier {
+    None,
+    Prefix(usize),
+    Explode,
+}
+
+/// Errors from parsing a URI Template.
+#[derive(Debug, PartialEq)]
+pub enum TemplateError {
+    /// An expression was opened but never closed.
+    UnclosedExpression(usize),
+    /// An expression was closed without having been opened.
+    UnopenedExpression(usize),
+    /// An expression contained an invalid variable specification.
+    InvalidVariable(String),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnclosedExpression(position) => {
+                write!(f, "Unclosed expression starting at position {position}")
+            }
+            Self::UnopenedExpression(position) => {
+                write!(f, "Unexpected end of expression at position {position}")
+            }
+            Self::InvalidVariable(variable) => write!(f, "Invalid variable: {variable}"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// The value of a single variable to expand a URI Template with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateValue {
+    String(String),
+    List(Vec<String>),
+    Map(Vec<(String, String)>),
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+macro_rules! template_value_from_display {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for TemplateValue {
+                fn from(value: $t) -> Self {
+                    Self::String(value.to_string())
+                }
+            }
+        )*
+    };
+}
+
+template_value_from_display!(bool, i32, i64, u32, u64, usize);
+
+impl<T> From<Vec<T>> for TemplateValue
+where
+    T: ToString,
+{
+    fn from(values: Vec<T>) -> Self {
+        Self::List(values.iter().map(ToString::to_string).collect())
+    }
+}
+
+impl<K, V> From<BTreeMap<K, V>> for TemplateValue
+where
+    K: ToString,
+    V: ToString,
+{
+    fn from(values: BTreeMap<K, V>) -> Self {
+        Self::Map(
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl TemplateValue {
+    /// Convert a JSON value into a template value.
+    /// Objects and arrays can only be converted if all of their members are simple values.
+    ///
+    /// # Returns
+    /// The template value, or `None` if the JSON value is null or can't be represented
+    fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Array(values) => values
+                .iter()
+                .map(simple_json)
+                .collect::<Option<_>>()
+                .map(Self::List),
+            Value::Object(values) => values
+                .iter()
+                .map(|(k, v)| simple_json(v).map(|v| (k.clone(), v)))
+                .collect::<Option<_>>()
+                .map(Self::Map),
+            value => simple_json(value).map(Self::String),
+        }
+    }
+}
+
+/// Convert a simple JSON value into a string, for use in a template value.
+fn simple_json(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// The set of values to expand a URI Template with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateValues(BTreeMap<String, TemplateValue>);
+
+impl TemplateValues {
+    /// Specify the value of a variable. Any variables that aren't specified are treated as undefined.
+    #[must_use]
+    pub fn with<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<TemplateValue>,
+    {
+        self.0.insert(name.into(), value.into());
+        self
+    }
+
+    /// Specify the value of a variable, if there is one.
+    #[must_use]
+    pub fn with_optional<N, V>(self, name: N, value: Option<V>) -> Self
+    where
+        N: Into<String>,
+        V: Into<TemplateValue>,
+    {
+        match value {
+            Some(value) => self.with(name, value),
+            None => self,
+        }
+    }
+}
+
+impl From<&Value> for TemplateValues {
+    /// Use the top-level members of a JSON object as the values to expand a URI Template with.
+    /// Any members that can't be represented are treated as undefined.
+    fn from(value: &Value) -> Self {
+        let values = value
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, value)| {
+                TemplateValue::from_json(value).map(|value| (name.clone(), value))
+            })
+            .collect();
+
+        Self(values)
+    }
+}
+
+impl Operator {
+    fn parse(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Self::Reserved),
+            '#' => Some(Self::Fragment),
+            '.' => Some(Self::Label),
+            '/' => Some(Self::Path),
+            ';' => Some(Self::PathParameter),
+            '?' => Some(Self::Query),
+            '&' => Some(Self::QueryContinuation),
+            _ => None,
+        }
+    }
+
+    /// The string to output before the first expanded variable.
+    fn first(self) -> &'static str {
+        match self {
+            Self::Simple | Self::Reserved => "",
+            Self::Fragment => "#",
+            Self::Label => ".",
+            Self::Path => "/",
+            Self::PathParameter => ";",
+            Self::Query => "?",
+            Self::QueryContinuation => "&",
+        }
+    }
+
+    /// The separator to output between expanded variables.
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Simple | Self::Reserved | Self::Fragment => ",",
+            Self::Label => ".",
+            Self::Path => "/",
+            Self::PathParameter => ";",
+            Self::Query | Self::QueryContinuation => "&",
+        }
+    }
+
+    /// Whether the expansion includes the variable names.
+    fn named(self) -> bool {
+        matches!(
+            self,
+            Self::PathParameter | Self::Query | Self::QueryContinuation
+        )
+    }
+
+    /// The string to output after the name of a variable with an empty value.
+    fn if_empty(self) -> &'static str {
+        match self {
+            Self::Query | Self::QueryContinuation => "=",
+            _ => "",
+        }
+    }
+
+    /// Whether reserved characters are allowed through unencoded.
+    fn allow_reserved(self) -> bool {
+        matches!(self, Self::Reserved | Self::Fragment)
+    }
+}
+
+impl VarSpec {
+    fn parse(spec: &str) -> Result<Self, TemplateError> {
+        let invalid = || TemplateError::InvalidVariable(spec.to_owned());
+
+        let (name, modifier) = if let Some(name) = spec.strip_suffix('*') {
+            (name, Modifier::Explode)
+        } else if let Some((name, length)) = spec.split_once(':') {
+            let length = length.parse::<usize>().map_err(|_| invalid())?;
+            if length == 0 || length > 9999 {
+                return Err(invalid());
+            }
+            (name, Modifier::Prefix(length))
+        } else {
+            (spec, Modifier::None)
+        };
+
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '%');
+        if !valid {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            modifier,
+        })
+    }
+}
+
+impl UriTemplate {
+    /// Parse a URI Template.
+    ///
+    /// # Parameters
+    /// - `template` - The template to parse
+    ///
+    /// # Returns
+    /// The parsed template
+    ///
+    /// # Errors
+    /// If the template is not valid
+    pub fn parse<S>(template: S) -> Result<Self, TemplateError>
+    where
+        S: Into<String>,
+    {
+        let template = template.into();
+        let mut parts = vec![];
+        let mut remaining = template.as_str();
+        let mut offset = 0;
+
+        while !remaining.is_empty() {
+            let open = remaining.find('{');
+            let close = remaining.find('}');
+
+            match (open, close) {
+                (None, Some(close)) => {
+                    return Err(TemplateError::UnopenedExpression(offset + close));
+                }
+                (Some(open), Some(close)) if close < open => {
+                    return Err(TemplateError::UnopenedExpression(offset + close));
+                }
+                (None, None) => {
+                    parts.push(Part::Literal(remaining.to_owned()));
+                    remaining = "";
+                }
+                (Some(open), None) => return Err(TemplateError::UnclosedExpression(offset + open)),
+                (Some(open), Some(close)) => {
+                    if open > 0 {
+                        parts.push(Part::Literal(remaining[..open].to_owned()));
+                    }
+
+                    let expression = &remaining[open + 1..close];
+                    if expression.contains('{') {
+                        return Err(TemplateError::UnclosedExpression(offset + open));
+                    }
+                    parts.push(parse_expression(expression)?);
+
+                    offset += close + 1;
+                    remaining = &remaining[close + 1..];
+                }
+            }
+        }
+
+        Ok(Self { template, parts })
+    }
+
+    /// Expand the template with the given values.
+    ///
+    /// # Parameters
+    /// - `values` - The values of the variables in the template
+    ///
+    /// # Returns
+    /// The expanded URI
+    #[must_use]
+    pub fn expand(&self, values: &TemplateValues) -> String {
+        let mut result = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => result.push_str(literal),
+                Part::Expression(operator, specs) => {
+                    expand_expression(&mut result, *operator, specs, values);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl std::fmt::Display for UriTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.template)
+    }
+}
+
+fn parse_expression(expression: &str) -> Result<Part, TemplateError> {
+    let mut chars = expression.chars();
+    let (operator, specs) = match chars.next().and_then(Operator::parse) {
+        Some(operator) => (operator, chars.as_str()),
+        None => (Operator::Simple, expression),
+    };
+
+    let specs = specs
+        .split(',')
+        .map(VarSpec::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Part::Expression(operator, specs))
+}
+
+fn expand_expression(
+    result: &mut String,
+    operator: Operator,
+    specs: &[VarSpec],
+    values: &TemplateValues,
+) {
+    let mut first = true;
+
+    for spec in specs {
+        let value = match values.0.get(&spec.name) {
+            None => continue,
+            Some(TemplateValue::List(list)) if list.is_empty() => continue,
+            Some(TemplateValue::Map(map)) if map.is_empty() => continue,
+            Some(value) => value,
+        };
+
+        result.push_str(if first {
+            operator.first()
+        } else {
+            operator.separator()
+        });
+        first = false;
+
+        let encode = |value: &str| encode(value, operator.allow_reserved());
+        let named_value = |result: &mut String, name: &str, value: &str| {
+            result.push_str(name);
+            if value.is_empty() {
+                result.push_str(operator.if_empty());
+            } else {
+                result.push('=');
+                result.push_str(value);
+            }
+        };
+
+        match (value, spec.modifier) {
+            (TemplateValue::String(value), modifier) => {
+                let value = match modifier {
+                    Modifier::Prefix(length) => value.chars().take(length).collect(),
+                    _ => value.clone(),
+                };
+                if operator.named() {
+                    named_value(result, &spec.name, &encode(&value));
+                } else {
+                    result.push_str(&encode(&value));
+                }
+            }
+            (TemplateValue::List(list), Modifier::Explode) => {
+                let items = list.iter().map(|item| {
+                    let item = encode(item);
+                    if operator.named() {
+                        let mut named = String::new();
+                        named_value(&mut named, &spec.name, &item);
+                        named
+                    } else {
+                        item
+                    }
+                });
+                result.push_str(&items.collect::<Vec<_>>().join(operator.separator()));
+            }
+            (TemplateValue::Map(map), Modifier::Explode) => {
+                let items = map.iter().map(|(key, value)| {
+                    let mut item = String::new();
+                    if operator.named() {
+                        named_value(&mut item, &encode(key), &encode(value));
+                    } else {
+                        item.push_str(&encode(key));
+                        item.push('=');
+                        item.push_str(&encode(value));
+                    }
+                    item
+                });
+                result.push_str(&items.collect::<Vec<_>>().join(operator.separator()));
+            }
+            (TemplateValue::List(list), _) => {
+                let joined = list.iter().map(|item| encode(item)).collect::<Vec<_>>();
+                if operator.named() {
+                    named_value(result, &spec.name, &joined.join(","));
+                } else {
+                    result.push_str(&joined.join(","));
+                }
+            }
+            (TemplateValue::Map(map), _) => {
+                let joined = map
+                    .iter()
+                    .flat_map(|(key, value)| vec![encode(key), encode(value)])
+                    .collect::<Vec<_>>();
+                if operator.named() {
+                    named_value(result, &spec.name, &joined.join(","));
+                } else {
+                    result.push_str(&joined.join(","));
+                }
+            }
+        }
+    }
+}
+
+/// Percent-encode a value for inclusion in an expanded template.
+///
+/// # Parameters
+/// - `value` - The value to encode
+/// - `allow_reserved` - Whether reserved characters and existing percent-encoded triplets are
+///   allowed through unencoded
+fn encode(value: &str, allow_reserved: bool) -> String {
+    const RESERVED: &str = ":/?#[]@!$&'()*+,;=";
+
+    let mut result = String::new();
+    for (index, c) in value.char_indices() {
+        let unreserved = c.is_ascii_alphanumeric() || "-._~".contains(c);
+        let reserved = allow_reserved && RESERVED.contains(c);
+        let triplet = allow_reserved
+            && c == '%'
+            && value
+                .get(index + 1..index + 3)
+                .is_some_and(|hex| hex.chars().all(|h| h.is_ascii_hexdigit()));
+
+        if unreserved || reserved || triplet {
+            result.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                write!(result, "%{byte:02X}").unwrap();
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::{check, let_assert};
+
+    fn values() -> TemplateValues {
+        let mut keys = BTreeMap::new();
+        keys.insert("comma", ",");
+        keys.insert("dot", ".");
+        keys.insert("semi", ";");
+
+        TemplateValues::default()
+            .with("var", "value")
+            .with("hello", "Hello World!")
+            .with("path", "/foo/bar")
+            .with("empty", "")
+            .with("x", 1024)
+            .with("y", 768)
+            .with("list", vec!["red", "green", "blue"])
+            .with("keys", keys)
+    }
+
+    fn expand(template: &str) -> String {
+        let_assert!(Ok(template) = UriTemplate::parse(template));
+        template.expand(&values())
+    }
+
+    #[test]
+    fn simple() {
+        check!(expand("{var}") == "value");
+        check!(expand("{hello}") == "Hello%20World%21");
+        check!(expand("{x,y}") == "1024,768");
+        check!(expand("{x,undef,y}") == "1024,768");
+        check!(expand("{var:3}") == "val");
+        check!(expand("{list}") == "red,green,blue");
+        check!(expand("{list*}") == "red,green,blue");
+        check!(expand("{keys}") == "comma,%2C,dot,.,semi,%3B");
+        check!(expand("{keys*}") == "comma=%2C,dot=.,semi=%3B");
+    }
+
+    #[test]
+    fn reserved() {
+        check!(expand("{+var}") == "value");
+        check!(expand("{+hello}") == "Hello%20World!");
+        check!(expand("{+path}/here") == "/foo/bar/here");
+        check!(expand("{+path:6}/here") == "/foo/b/here");
+        check!(expand("{+keys*}") == "comma=,,dot=.,semi=;");
+    }
+
+    #[test]
+    fn fragment() {
+        check!(expand("X{#var}") == "X#value");
+        check!(expand("{#path:6}/here") == "#/foo/b/here");
+        check!(expand("{#list*}") == "#red,green,blue");
+    }
+
+    #[test]
+    fn label() {
+        check!(expand("X{.var}") == "X.value");
+        check!(expand("X{.x,y}") == "X.1024.768");
+        check!(expand("X{.list*}") == "X.red.green.blue");
+        check!(expand("X{.empty}") == "X.");
+    }
+
+    #[test]
+    fn path() {
+        check!(expand("{/var}") == "/value");
+        check!(expand("{/var,x}/here") == "/value/1024/here");
+        check!(expand("{/list*,path:4}") == "/red/green/blue/%2Ffoo");
+        check!(expand("{/keys*}") == "/comma=%2C/dot=./semi=%3B");
+    }
+
+    #[test]
+    fn path_parameters() {
+        check!(expand("{;x,y}") == ";x=1024;y=768");
+        check!(expand("{;x,y,empty}") == ";x=1024;y=768;empty");
+        check!(expand("{;list*}") == ";list=red;list=green;list=blue");
+        check!(expand("{;keys*}") == ";comma=%2C;dot=.;semi=%3B");
+    }
+
+    #[test]
+    fn query() {
+        check!(expand("{?x,y}") == "?x=1024&y=768");
+        check!(expand("{?x,y,empty}") == "?x=1024&y=768&empty=");
+        check!(expand("{?var:3}") == "?var=val");
+        check!(expand("{?list}") == "?list=red,green,blue");
+        check!(expand("{?list*}") == "?list=red&list=green&list=blue");
+        check!(expand("{?keys}") == "?keys=comma,%2C,dot,.,semi,%3B");
+        check!(expand("{?keys*}") == "?comma=%2C&dot=.&semi=%3B");
+        check!(expand("/users{?undef}") == "/users");
+        check!(expand("?fixed=yes{&x}") == "?fixed=yes&x=1024");
+    }
+
+    #[test]
+    fn display() {
+        let_assert!(Ok(template) = UriTemplate::parse("/users/{id}{?name,page}"));
+
+        check!(template.to_string() == "/users/{id}{?name,page}");
+    }
+
+    #[test]
+    fn typed_values() {
+        let_assert!(Ok(template) = UriTemplate::parse("/users{?name,page,active}"));
+        let values = TemplateValues::default()
+            .with_optional("name", Some("Graham Cox"))
+            .with("page", 2_u32)
+            .with("active", true);
+
+        check!(template.expand(&values) == "/users?name=Graham%20Cox&page=2&active=true");
+    }
+
+    #[test]
+    fn json_values() {
+        let_assert!(
+            Ok(template) = UriTemplate::parse("/users/{id}{?name,tags,keys*,missing,nested}")
+        );
+        let values = TemplateValues::from(&serde_json::json!({
+            "id": 1,
+            "name": "Graham Cox",
+            "tags": ["a", "b"],
+            "keys": { "active": true },
+            "missing": null,
+            "nested": [{ "a": 1 }]
+        }));
+
+        check!(template.expand(&values) == "/users/1?name=Graham%20Cox&tags=a,b&active=true");
+    }
+
+    #[test]
+    fn invalid_templates() {
+        check!(UriTemplate::parse("/users{?name") == Err(TemplateError::UnclosedExpression(6)));
+        check!(UriTemplate::parse("/users}") == Err(TemplateError::UnopenedExpression(6)));
+        check!(UriTemplate::parse("/users{a{b}") == Err(TemplateError::UnclosedExpression(6)));
+        check!(
+            UriTemplate::parse("/users{na me}")
+                == Err(TemplateError::InvalidVariable("na me".to_owned()))
+        );
+        check!(
+            UriTemplate::parse("/users{name:0}")
+                == Err(TemplateError::InvalidVariable("name:0".to_owned()))
+        );
+        check!(
+            UriTemplate::parse("/users{}") == Err(TemplateError::InvalidVariable(String::new()))
+        );
+    }
+}
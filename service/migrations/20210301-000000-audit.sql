@@ -0,0 +1,13 @@
+CREATE TABLE audit_log(
+  audit_id BIGSERIAL PRIMARY KEY,
+  entity_type TEXT NOT NULL,
+  entity_id TEXT NOT NULL,
+  action TEXT NOT NULL,
+  before JSONB NULL,
+  after JSONB NULL,
+  principal TEXT NULL,
+  trace_id TEXT NULL,
+  recorded TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX audit_log_entity_idx ON audit_log(entity_type, entity_id, audit_id);
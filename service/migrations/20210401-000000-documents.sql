@@ -0,0 +1,6 @@
+CREATE TABLE documents(
+  id INTEGER PRIMARY KEY,
+  version UUID NOT NULL,
+  title TEXT NOT NULL,
+  modified TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+mod authorization;
+pub(super) mod component;
+mod context;
+mod http;
@@ -0,0 +1,41 @@
+use crate::http::Principal;
+
+/// The principals that are allowed to browse the audit history.
+/// The history contains complete snapshots of every audited row, so by default nobody is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Auditors(Vec<String>);
+
+impl Auditors {
+    /// Create the set of principals that are allowed to browse the audit history.
+    pub fn new(principals: Vec<String>) -> Self {
+        Self(principals)
+    }
+
+    /// Check whether a request may browse the audit history.
+    ///
+    /// # Parameters
+    /// - `principal` - The principal making the request, or `None` for anonymous requests
+    pub fn permits(&self, principal: Option<&Principal>) -> bool {
+        principal.is_some_and(|principal| self.0.contains(&principal.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[test]
+    fn permits() {
+        let auditors = Auditors::new(vec!["graham".to_owned()]);
+
+        check!(auditors.permits(Some(&Principal("graham".to_owned()))));
+        check!(!auditors.permits(Some(&Principal("other".to_owned()))));
+        check!(!auditors.permits(None));
+    }
+
+    #[test]
+    fn nobody_by_default() {
+        check!(!Auditors::default().permits(Some(&Principal("graham".to_owned()))));
+    }
+}
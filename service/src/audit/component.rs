@@ -0,0 +1,54 @@
+use super::authorization::Auditors;
+use crate::database::Database;
+use crate::http::routes::Routes;
+use crate::server::Configurer;
+use actix_web::web::ServiceConfig;
+use std::sync::Arc;
+
+/// Component for browsing the audit history of entities
+pub struct Component {
+    database: Arc<Database>,
+    auditors: Auditors,
+}
+
+/// Builder to build the audit component
+pub struct Builder {
+    database: Arc<Database>,
+    auditors: Auditors,
+}
+
+impl Builder {
+    /// Create a new builder for the audit component, reading history from the given database.
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            auditors: Auditors::default(),
+        }
+    }
+
+    /// Specify the principals that are allowed to browse the audit history.
+    pub fn with_auditors(mut self, principals: Vec<String>) -> Self {
+        self.auditors = Auditors::new(principals);
+        self
+    }
+
+    /// Build the actual audit component.
+    pub fn build(self) -> Arc<Component> {
+        Arc::new(Component {
+            database: self.database,
+            auditors: self.auditors,
+        })
+    }
+}
+
+impl Configurer for Component {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config.data(self.database.clone());
+        config.data(self.auditors.clone());
+        super::http::configure_server(config);
+    }
+
+    fn register_routes(&self, routes: &mut Routes) {
+        super::http::register_routes(routes);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::database::audit::AuditContext;
+use crate::http::Principal;
+use crate::server::TraceId;
+use actix_http::Payload;
+use actix_web::{FromRequest, HttpRequest};
+use futures::future::{ok, Ready};
+
+impl FromRequest for AuditContext {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(Self {
+            principal: Principal::for_request(req).map(|p| p.0),
+            trace_id: req.extensions().get::<TraceId>().map(|t| t.0.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::PrincipalHeader;
+    use actix_http::http::header::HeaderName;
+    use actix_web::test::TestRequest;
+    use assert2::{check, let_assert};
+
+    #[actix_rt::test]
+    async fn anonymous_untraced() {
+        let (req, mut payload) = TestRequest::default().to_http_parts();
+
+        let_assert!(Ok(context) = AuditContext::from_request(&req, &mut payload).await);
+        check!(context == AuditContext::default());
+    }
+
+    #[actix_rt::test]
+    async fn authenticated_traced() {
+        let (req, mut payload) = TestRequest::default()
+            .data(PrincipalHeader(HeaderName::from_static("x-principal")))
+            .header("x-principal", "graham")
+            .to_http_parts();
+        req.extensions_mut()
+            .insert(TraceId("4bf92f3577b34da6a3ce929d0e0e4736".to_owned()));
+
+        let_assert!(Ok(context) = AuditContext::from_request(&req, &mut payload).await);
+        check!(context.principal == Some("graham".to_owned()));
+        check!(context.trace_id == Some("4bf92f3577b34da6a3ce929d0e0e4736".to_owned()));
+    }
+}
@@ -0,0 +1,16 @@
+use crate::http::routes::{Route, Routes};
+use actix_web::web::{get, ServiceConfig};
+
+mod get;
+
+/// The route for the audit history of a single entity.
+pub const AUDIT_HISTORY: Route =
+    Route::new("audit-history", "/audit/{entity_type}/{entity_id}").with_query(&["cursor"]);
+
+pub fn register_routes(routes: &mut Routes) {
+    routes.register(AUDIT_HISTORY);
+}
+
+pub fn configure_server(config: &mut ServiceConfig) {
+    config.service(AUDIT_HISTORY.resource().route(get().to(get::handle)));
+}
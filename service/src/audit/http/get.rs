@@ -0,0 +1,107 @@
+use super::AUDIT_HISTORY;
+use crate::audit::authorization::Auditors;
+use crate::database::{
+    audit::{AuditEntry, AuditHistory},
+    pagination::Cursor,
+    Database,
+};
+use crate::http::{
+    hal::{HalCollection, HalDocument, HalResource},
+    problem::Problem,
+    uri_template::TemplateValues,
+    urls::UrlBuilder,
+    Principal, Response, SimpleRespondable,
+};
+use actix_http::http::StatusCode;
+use actix_web::{
+    web::{Data, Path, Query},
+    HttpRequest,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, HalResource)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryDocument {
+    pub action: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub principal: Option<String>,
+    pub trace_id: Option<String>,
+    pub recorded: DateTime<Utc>,
+}
+
+impl From<AuditEntry> for EntryDocument {
+    fn from(entry: AuditEntry) -> Self {
+        Self {
+            action: entry.action,
+            before: entry.before,
+            after: entry.after,
+            principal: entry.principal,
+            trace_id: entry.trace_id,
+            recorded: entry.recorded,
+        }
+    }
+}
+
+pub async fn handle(
+    req: HttpRequest,
+    path: Path<(String, String)>,
+    query: Query<PageQuery>,
+    database: Data<Arc<Database>>,
+    auditors: Data<Auditors>,
+    urls: UrlBuilder,
+) -> Result<Response<SimpleRespondable<HalDocument>>, Problem> {
+    if !auditors.permits(Principal::for_request(&req).as_ref()) {
+        return Err(Problem::new(StatusCode::FORBIDDEN));
+    }
+
+    let (entity_type, entity_id) = path.into_inner();
+
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(Cursor::<i64, i64>::decode)
+        .transpose()?;
+
+    let mut conn = database.connect().await;
+    let tx = conn.begin().await;
+    let AuditHistory {
+        entries,
+        prev,
+        next,
+    } = tx
+        .list_changes(&entity_type, &entity_id, cursor.as_ref())
+        .await?;
+    tx.commit().await?;
+
+    let template = urls.route_template(&AUDIT_HISTORY)?;
+
+    let hal_document = HalDocument::from(
+        HalCollection::cursor(
+            entries.into_iter().map(EntryDocument::from).collect(),
+            template,
+            cursor.as_ref(),
+            prev.as_ref(),
+            next.as_ref(),
+        )
+        .with_values(
+            TemplateValues::default()
+                .with("entity_type", entity_type)
+                .with("entity_id", entity_id),
+        ),
+    )
+    .resolve_links(&urls);
+
+    Ok(SimpleRespondable::from(hal_document)
+        .with_link_header(vec!["self", "first", "prev", "next"])
+        .with_vary(urls.vary().to_vec())
+        .into())
+}
@@ -3,71 +3,50 @@ use std::sync::Arc;
 use deadpool::managed::Object;
 use deadpool_postgres::{ClientWrapper, Pool};
 use postgres_types::ToSql;
-use prometheus::{IntCounter, IntCounterVec, IntGauge, Opts, Registry};
 use tokio_postgres::{IsolationLevel, Row};
 
+pub(super) mod audit;
 pub(super) mod component;
-mod migrate;
+pub(super) mod fixtures;
+mod metrics;
+pub(super) mod migrate;
+pub(super) mod pagination;
+mod sqlcommenter;
+pub(super) mod versioned;
 
-struct Metrics {
-    connection_gauge: IntGauge,
-    transaction_gauge: IntGauge,
-    connection_count: IntCounter,
-    transaction_count: IntCounterVec,
-}
+use metrics::Metrics;
 
 /// Wrapper around a database connection pool
 pub struct Database {
     pool: Pool,
     metrics: Arc<Metrics>,
+    sql_comments: bool,
 }
 
 impl Database {
-    fn new(pool: Pool, prometheus: &Registry) -> Self {
-        let connection_gauge =
-            IntGauge::new("conections_active", "Number of connections checked out").unwrap();
-        let transaction_gauge =
-            IntGauge::new("transactions_active", "Number of active transactions").unwrap();
-        let connection_count =
-            IntCounter::new("connections", "Number of connections ever issued").unwrap();
-        let transaction_count = IntCounterVec::new(
-            Opts::new(
-                "transactions_states",
-                "Number of transactions ever issued by status",
-            ),
-            &["status"],
-        )
-        .unwrap();
-
-        prometheus
-            .register(Box::new(connection_gauge.clone()))
-            .unwrap();
-        prometheus
-            .register(Box::new(transaction_gauge.clone()))
-            .unwrap();
-        prometheus
-            .register(Box::new(connection_count.clone()))
-            .unwrap();
-        prometheus
-            .register(Box::new(transaction_count.clone()))
-            .unwrap();
-
+    fn new(pool: Pool, metrics: Metrics, sql_comments: bool) -> Self {
         Self {
             pool,
-            metrics: Arc::new(Metrics {
-                connection_gauge,
-                transaction_gauge,
-                connection_count,
-                transaction_count,
-            }),
+            metrics: Arc::new(metrics),
+            sql_comments,
         }
     }
 }
-/// Wrapper around a connection to the database
-pub struct Connection(Object<ClientWrapper, tokio_postgres::Error>, Arc<Metrics>);
 
-/// Wrapper around a database transaction
-pub struct Transaction<'a>(Option<deadpool_postgres::Transaction<'a>>, Arc<Metrics>);
+/// Wrapper around a connection to the database
+pub struct Connection(
+    Object<ClientWrapper, tokio_postgres::Error>,
+    Arc<Metrics>,
+    bool,
+);
+
+/// Wrapper around a database transaction.
+/// The final field indicates whether statements should have a sqlcommenter comment appended to them.
+pub struct Transaction<'a>(
+    Option<deadpool_postgres::Transaction<'a>>,
+    Arc<Metrics>,
+    bool,
+);
 
 impl Database {
     /// Get a new connection to the database from the connection pool
@@ -79,10 +58,9 @@ impl Database {
             .await
             .expect("Failed to get database connection");
 
-        self.metrics.connection_gauge.inc();
-        self.metrics.connection_count.inc();
+        self.metrics.connection_opened();
 
-        Connection(conn, self.metrics.clone())
+        Connection(conn, self.metrics.clone(), self.sql_comments)
     }
 }
 
@@ -101,36 +79,32 @@ impl Connection {
             .await
             .expect("Failed to start transaction");
 
-        self.1.transaction_gauge.inc();
-        self.1.transaction_count.with_label_values(&["start"]).inc();
+        self.1.transaction_started();
 
-        Transaction(Some(transaction), self.1.clone())
+        Transaction(Some(transaction), self.1.clone(), self.2)
     }
 }
 
 impl Drop for Connection {
     fn drop(&mut self) {
         tracing::debug!("Returning database connection");
-        self.1.connection_gauge.dec();
+        self.1.connection_closed();
     }
 }
 
-impl<'a> Drop for Transaction<'a> {
+impl Drop for Transaction<'_> {
     fn drop(&mut self) {
         tracing::debug!("Finishing transaction");
-        self.1.transaction_gauge.dec();
+        self.1.transaction_closed();
 
         if self.0.is_some() {
             tracing::warn!("Transaction was not committed and will be rolled back");
-            self.1
-                .transaction_count
-                .with_label_values(&["rollback"])
-                .inc();
+            self.1.transaction_finished("rollback");
         }
     }
 }
 
-impl<'a> Transaction<'a> {
+impl Transaction<'_> {
     /// Execute a SQL statement within the transaction
     ///
     /// # Parameters
@@ -157,6 +131,7 @@ impl<'a> Transaction<'a> {
         );
         let _enter = span.enter();
 
+        let sql = self.annotate(sql, &span);
         let tx = self.0.as_ref().unwrap();
         let result = tx.execute(sql.as_str(), params).await;
 
@@ -188,6 +163,7 @@ impl<'a> Transaction<'a> {
         );
         let _enter = span.enter();
 
+        let sql = self.annotate(sql, &span);
         let tx = self.0.as_ref().unwrap();
         let result = tx.batch_execute(sql.as_str()).await;
 
@@ -222,6 +198,7 @@ impl<'a> Transaction<'a> {
         );
         let _enter = span.enter();
 
+        let sql = self.annotate(sql, &span);
         let tx = self.0.as_ref().unwrap();
         let result = tx.query(sql.as_str(), params).await;
 
@@ -247,13 +224,71 @@ impl<'a> Transaction<'a> {
         let tx = self.0.take().unwrap();
         let result = tx.commit().await;
 
-        self.1
-            .transaction_count
-            .with_label_values(&["commit"])
-            .inc();
+        self.1.transaction_finished("commit");
+
+        span.record("error", &result.is_err());
+
+        result
+    }
+
+    /// Roll back the transaction.
+    /// This consumes the transaction object, after which it is not usable.
+    pub async fn rollback(mut self) -> Result<(), tokio_postgres::Error> {
+        let span = tracing::trace_span!(
+            "database::Transaction::rollback",
+            error = tracing::field::Empty,
+        );
+        let _enter = span.enter();
+
+        let tx = self.0.take().unwrap();
+        let result = tx.rollback().await;
+
+        self.1.transaction_finished("rollback");
 
         span.record("error", &result.is_err());
 
         result
     }
+
+    /// Append a sqlcommenter comment to the SQL, if this is enabled.
+    fn annotate(&self, sql: String, span: &tracing::Span) -> String {
+        if self.2 {
+            sqlcommenter::annotate(sql, span)
+        } else {
+            sql
+        }
+    }
+}
+
+/// Quote an identifier - e.g. a table or column name - for safe use in a SQL statement.
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Quote the name of a table, which may be qualified by its schema, for safe use in a SQL
+/// statement.
+fn quote_table(table: &str) -> String {
+    table
+        .split('.')
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[test]
+    fn quote_identifiers() {
+        check!(quote_identifier("users") == r#""users""#);
+        check!(quote_identifier(r#"my "users""#) == r#""my ""users""""#);
+    }
+
+    #[test]
+    fn quote_tables() {
+        check!(quote_table("users") == r#""users""#);
+        check!(quote_table("public.users") == r#""public"."users""#);
+    }
 }
@@ -0,0 +1,302 @@
+use super::{
+    pagination::{Cursor, KeysetQuery, Order, Page},
+    quote_identifier, quote_table,
+    versioned::{UpdateError, ID_COLUMN},
+    Transaction,
+};
+use chrono::{DateTime, Utc};
+use postgres_types::ToSql;
+use serde_json::Value;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+/// Details of who made a change, and as part of what request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditContext {
+    /// The authenticated principal that made the change, if there was one.
+    pub principal: Option<String>,
+    /// The ID of the trace that the change was made as part of, if it was being traced.
+    pub trace_id: Option<String>,
+}
+
+/// The entity that a change is recorded against in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuditedEntity<'a> {
+    /// The type of entity.
+    pub entity_type: &'a str,
+    /// The ID of the entity.
+    pub entity_id: &'a str,
+}
+
+/// A change that was made to an entity, described by snapshots of it from before and after.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change<'a> {
+    /// The entity was created.
+    Created { after: &'a Value },
+    /// The entity was updated.
+    Updated { before: &'a Value, after: &'a Value },
+    /// The entity was deleted.
+    Deleted { before: &'a Value },
+}
+
+impl<'a> Change<'a> {
+    /// Work out the change that was made to an entity from snapshots of it.
+    ///
+    /// # Parameters
+    /// - `before` - The snapshot of the entity before the change, if it existed
+    /// - `after` - The snapshot of the entity after the change, if it still exists
+    ///
+    /// # Returns
+    /// The change, or `None` if the entity didn't exist either before or after, so that nothing
+    /// changed
+    pub fn from_snapshots(before: Option<&'a Value>, after: Option<&'a Value>) -> Option<Self> {
+        match (before, after) {
+            (None, None) => None,
+            (None, Some(after)) => Some(Self::Created { after }),
+            (Some(before), Some(after)) => Some(Self::Updated { before, after }),
+            (Some(before), None) => Some(Self::Deleted { before }),
+        }
+    }
+
+    /// The action to record the change as.
+    fn action(self) -> &'static str {
+        match self {
+            Self::Created { .. } => "created",
+            Self::Updated { .. } => "updated",
+            Self::Deleted { .. } => "deleted",
+        }
+    }
+
+    /// The snapshot of the entity before the change, if it existed.
+    fn before(self) -> Option<&'a Value> {
+        match self {
+            Self::Created { .. } => None,
+            Self::Updated { before, .. } | Self::Deleted { before } => Some(before),
+        }
+    }
+
+    /// The snapshot of the entity after the change, if it still exists.
+    fn after(self) -> Option<&'a Value> {
+        match self {
+            Self::Created { after } | Self::Updated { after, .. } => Some(after),
+            Self::Deleted { .. } => None,
+        }
+    }
+}
+
+/// A single recorded change to an entity.
+#[derive(Debug)]
+pub struct AuditEntry {
+    pub action: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub principal: Option<String>,
+    pub trace_id: Option<String>,
+    pub recorded: DateTime<Utc>,
+}
+
+impl From<&Row> for AuditEntry {
+    fn from(row: &Row) -> Self {
+        Self {
+            action: row.get("action"),
+            before: row.get("before"),
+            after: row.get("after"),
+            principal: row.get("principal"),
+            trace_id: row.get("trace_id"),
+            recorded: row.get("recorded"),
+        }
+    }
+}
+
+/// A page of the recorded changes to a single entity.
+pub struct AuditHistory {
+    /// The changes on this page, most recent first.
+    pub entries: Vec<AuditEntry>,
+    /// The cursor for the page of more recent changes, if there is one.
+    pub prev: Option<Cursor<i64, i64>>,
+    /// The cursor for the page of older changes, if there is one.
+    pub next: Option<Cursor<i64, i64>>,
+}
+
+/// The query to list the changes to a single entity, most recent first.
+const HISTORY_QUERY: KeysetQuery = KeysetQuery {
+    sql: "SELECT * FROM audit_log WHERE entity_type = $1 AND entity_id = $2",
+    sort_column: "audit_id",
+    id_column: "audit_id",
+    order: Order::Descending,
+    limit: 20,
+};
+
+impl Transaction<'_> {
+    /// Take a snapshot of a single row of a table, as a JSON object.
+    ///
+    /// # Parameters
+    /// - `table` - The table that the row is in
+    /// - `id` - The ID of the row
+    ///
+    /// # Returns
+    /// The snapshot of the row, or `None` if it doesn't exist
+    pub async fn snapshot(
+        &self,
+        table: &str,
+        id: &(dyn ToSql + Sync),
+    ) -> Result<Option<Value>, tokio_postgres::Error> {
+        let rows = self
+            .query(
+                format!(
+                    "SELECT to_jsonb(t) FROM {} AS t WHERE t.{} = $1",
+                    quote_table(table),
+                    quote_identifier(ID_COLUMN)
+                ),
+                &[id],
+            )
+            .await?;
+
+        Ok(rows.first().map(|row| row.get(0)))
+    }
+
+    /// Record a change to an entity in the audit log.
+    ///
+    /// # Parameters
+    /// - `context` - Details of who made the change
+    /// - `entity` - The entity that was changed
+    /// - `change` - The change that was made to the entity
+    pub async fn record_change(
+        &self,
+        context: &AuditContext,
+        entity: AuditedEntity<'_>,
+        change: Change<'_>,
+    ) -> Result<(), tokio_postgres::Error> {
+        self.execute(
+            "INSERT INTO audit_log(entity_type, entity_id, action, before, after, principal, trace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            &[
+                &entity.entity_type,
+                &entity.entity_id,
+                &change.action(),
+                &change.before(),
+                &change.after(),
+                &context.principal,
+                &context.trace_id,
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Update a single versioned resource in the same way as `update_versioned`, recording the
+    /// change in the audit log.
+    ///
+    /// # Parameters
+    /// - `context` - Details of who is making the change
+    /// - `entity` - The entity that is being changed, as recorded in the audit log
+    /// - `table` - The table that the resource is stored in
+    /// - `id` - The ID of the resource
+    /// - `version` - The version that the resource is expected to be at
+    /// - `changes` - The columns to update, and the new values for them
+    ///
+    /// # Returns
+    /// The entire row for the resource after it was updated
+    ///
+    /// # Errors
+    /// If the resource doesn't exist, or isn't at the expected version, or the update itself fails
+    pub async fn update_versioned_audited(
+        &self,
+        context: &AuditContext,
+        entity: AuditedEntity<'_>,
+        table: &str,
+        id: &(dyn ToSql + Sync),
+        version: Uuid,
+        changes: &[(&str, &(dyn ToSql + Sync))],
+    ) -> Result<Row, UpdateError> {
+        let before = self.snapshot(table, id).await?;
+        let row = self.update_versioned(table, id, version, changes).await?;
+        let after = self.snapshot(table, id).await?;
+
+        if let Some(change) = Change::from_snapshots(before.as_ref(), after.as_ref()) {
+            self.record_change(context, entity, change).await?;
+        }
+
+        Ok(row)
+    }
+
+    /// List the recorded changes to a single entity, most recent first.
+    ///
+    /// # Parameters
+    /// - `entity_type` - The type of entity
+    /// - `entity_id` - The ID of the entity
+    /// - `cursor` - The cursor to start from. If not provided then the most recent changes are returned
+    ///
+    /// # Returns
+    /// The page of changes
+    pub async fn list_changes(
+        &self,
+        entity_type: &str,
+        entity_id: &str,
+        cursor: Option<&Cursor<i64, i64>>,
+    ) -> Result<AuditHistory, tokio_postgres::Error> {
+        let Page { rows, prev, next } = self
+            .query_page(&HISTORY_QUERY, cursor, &[&entity_type, &entity_id])
+            .await?;
+
+        Ok(AuditHistory {
+            entries: rows.iter().map(AuditEntry::from).collect(),
+            prev,
+            next,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+    use serde_json::json;
+
+    #[test]
+    fn change_from_snapshots() {
+        let snapshot = json!({"id": 1});
+
+        check!(
+            Change::from_snapshots(None, Some(&snapshot))
+                == Some(Change::Created { after: &snapshot })
+        );
+        check!(
+            Change::from_snapshots(Some(&snapshot), Some(&snapshot))
+                == Some(Change::Updated {
+                    before: &snapshot,
+                    after: &snapshot
+                })
+        );
+        check!(
+            Change::from_snapshots(Some(&snapshot), None)
+                == Some(Change::Deleted { before: &snapshot })
+        );
+        check!(Change::from_snapshots(None, None) == None);
+    }
+
+    #[test]
+    fn change_snapshots() {
+        let before = json!({"id": 1, "title": "Before"});
+        let after = json!({"id": 1, "title": "After"});
+
+        let created = Change::Created { after: &after };
+        check!(created.action() == "created");
+        check!(created.before() == None);
+        check!(created.after() == Some(&after));
+
+        let updated = Change::Updated {
+            before: &before,
+            after: &after,
+        };
+        check!(updated.action() == "updated");
+        check!(updated.before() == Some(&before));
+        check!(updated.after() == Some(&after));
+
+        let deleted = Change::Deleted { before: &before };
+        check!(deleted.action() == "deleted");
+        check!(deleted.before() == Some(&before));
+        check!(deleted.after() == None);
+    }
+}
@@ -1,21 +1,96 @@
-use std::{str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
 use prometheus::Registry;
 
-use super::Database;
+use super::{migrate::LockError, Database, Metrics};
 
 /// Component to represent the database connection
 pub struct Component {
     pub database: Arc<Database>,
 }
 
-impl Component {
-    /// Create a new database component.
-    #[tracing::instrument(name = "Database::Component::new", skip(prometheus))]
-    pub async fn new(url: &str, prometheus: &Registry) -> Self {
+/// Builder to build the database component
+pub struct Builder {
+    url: String,
+    name: String,
+    metrics_namespace: String,
+    legacy_metrics: bool,
+    sql_comments: bool,
+    migrate: bool,
+    migration_lock_timeout: Duration,
+}
+
+impl Builder {
+    /// Create a new builder for the database at the given URL.
+    pub fn new<S>(url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            url: url.into(),
+            name: "primary".to_owned(),
+            metrics_namespace: "bigbang".to_owned(),
+            legacy_metrics: true,
+            sql_comments: false,
+            migrate: true,
+            migration_lock_timeout: Duration::from_mins(1),
+        }
+    }
+
+    /// Specify the name of the database, used to tell it apart from any others in the metrics.
+    #[allow(dead_code)]
+    pub fn with_name<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.name = name.into();
+        self
+    }
+
+    /// Specify the namespace to register the database metrics under.
+    pub fn with_metrics_namespace<S>(mut self, namespace: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.metrics_namespace = namespace.into();
+        self
+    }
+
+    /// Specify whether to also register the metrics under their deprecated, un-namespaced names.
+    pub fn with_legacy_metrics(mut self, legacy_metrics: bool) -> Self {
+        self.legacy_metrics = legacy_metrics;
+        self
+    }
+
+    /// Specify whether to append sqlcommenter comments describing the current request and trace
+    /// to every SQL statement.
+    pub fn with_sql_comments(mut self, sql_comments: bool) -> Self {
+        self.sql_comments = sql_comments;
+        self
+    }
+
+    /// Specify whether to migrate the database schema to the latest version when building.
+    pub fn with_migrate(mut self, migrate: bool) -> Self {
+        self.migrate = migrate;
+        self
+    }
+
+    /// Specify how long to wait for any other instance that is already migrating the database.
+    pub fn with_migration_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.migration_lock_timeout = timeout;
+        self
+    }
+
+    /// Build the actual database component.
+    ///
+    /// # Errors
+    /// If the database schema needed migrating, but another instance held the migrations lock for
+    /// longer than the timeout
+    #[tracing::instrument(name = "Database::Component::new", skip(self, prometheus), fields(url = %self.url, name = %self.name))]
+    pub async fn build(self, prometheus: &Registry) -> Result<Component, LockError> {
         tracing::debug!("Building database connection");
-        let pg_config = tokio_postgres::Config::from_str(url).expect("Invalid database URL");
+        let pg_config = tokio_postgres::Config::from_str(&self.url).expect("Invalid database URL");
 
         let mgr_config = ManagerConfig {
             recycling_method: RecyclingMethod::Fast,
@@ -29,12 +104,20 @@ impl Component {
 
         tracing::debug!("Built database connection");
 
-        let db = Database::new(pool, prometheus);
+        let metrics = Metrics::new(
+            prometheus,
+            &self.metrics_namespace,
+            &self.name,
+            self.legacy_metrics,
+        );
+        let db = Database::new(pool, metrics, self.sql_comments);
 
-        super::migrate::migrate(&db).await;
+        if self.migrate {
+            super::migrate::migrate(&db, self.migration_lock_timeout).await?;
+        }
 
-        Self {
+        Ok(Component {
             database: Arc::new(db),
-        }
+        })
     }
 }
@@ -0,0 +1,221 @@
+use super::{quote_identifier, quote_table, Database, Transaction};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// A set of data that can be loaded into the database.
+#[derive(Debug, PartialEq)]
+pub enum Fixture {
+    /// A SQL script to execute.
+    Sql(String),
+    /// Rows to insert, grouped by the table to insert them into, in the order they are to be inserted.
+    Rows(Vec<(String, Vec<Map<String, Value>>)>),
+}
+
+/// Errors that can occur when loading fixtures.
+#[derive(Debug)]
+pub enum FixtureError {
+    /// The fixture file could not be read.
+    Io(PathBuf, std::io::Error),
+    /// The fixture file is not of a supported type.
+    UnsupportedFormat(PathBuf),
+    /// The fixture file could not be parsed.
+    Parse(PathBuf, String),
+    /// The fixture could not be applied to the database.
+    Database(PathBuf, tokio_postgres::Error),
+    /// The fixtures could not be committed to the database.
+    Commit(tokio_postgres::Error),
+}
+
+impl std::fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "Failed to read fixture {}: {}", path.display(), e),
+            Self::UnsupportedFormat(path) => {
+                write!(f, "Unsupported fixture format: {}", path.display())
+            }
+            Self::Parse(path, e) => write!(f, "Failed to parse fixture {}: {}", path.display(), e),
+            Self::Database(path, e) => {
+                write!(f, "Failed to apply fixture {}: {}", path.display(), e)
+            }
+            Self::Commit(e) => write!(f, "Failed to commit fixtures: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
+impl Fixture {
+    /// Load a fixture from a file.
+    /// Files ending `.sql` are treated as SQL scripts, and files ending `.yaml`, `.yml` or `.json`
+    /// are treated as rows to insert, keyed by table name.
+    ///
+    /// # Parameters
+    /// - `path` - The path to the fixture file
+    ///
+    /// # Returns
+    /// The fixture that was loaded
+    ///
+    /// # Errors
+    /// If the file couldn't be read or wasn't a valid fixture
+    pub fn load(path: &Path) -> Result<Self, FixtureError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| FixtureError::Io(path.to_owned(), e))?;
+
+        let extension = path.extension().and_then(std::ffi::OsStr::to_str);
+        let tables = match extension {
+            Some("sql") => return Ok(Self::Sql(contents)),
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+            _ => return Err(FixtureError::UnsupportedFormat(path.to_owned())),
+        };
+
+        tables
+            .and_then(Self::from_tables)
+            .map_err(|e| FixtureError::Parse(path.to_owned(), e))
+    }
+
+    /// Build a fixture from a map of table name to the rows to insert into that table.
+    fn from_tables(tables: Map<String, Value>) -> Result<Self, String> {
+        let rows = tables
+            .into_iter()
+            .map(|(table, rows)| {
+                serde_json::from_value(rows)
+                    .map(|rows| (table.clone(), rows))
+                    .map_err(|e| format!("Invalid rows for table {table}: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::Rows(rows))
+    }
+
+    /// Apply this fixture to the database.
+    ///
+    /// # Parameters
+    /// - `tx` - The transaction to apply the fixture in
+    ///
+    /// # Errors
+    /// If any of the SQL for the fixture failed to execute
+    pub async fn apply(&self, tx: &Transaction<'_>) -> Result<(), tokio_postgres::Error> {
+        match self {
+            Self::Sql(sql) => tx.batch_execute(sql.as_str()).await,
+            Self::Rows(tables) => {
+                for (table, rows) in tables {
+                    for row in rows {
+                        tx.execute(insert_statement(table, row), &[&Value::from(row.clone())])
+                            .await?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Build the SQL to insert a single row into a table.
+/// The row itself is bound as JSON, and Postgres converts the values to the correct column types.
+/// Only the columns present in the row are inserted, so any others get their default values.
+fn insert_statement(table: &str, row: &Map<String, Value>) -> String {
+    let table = quote_table(table);
+    let columns = row
+        .keys()
+        .map(|column| quote_identifier(column))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("INSERT INTO {table}({columns}) SELECT {columns} FROM json_populate_record(NULL::{table}, $1)")
+}
+
+/// Load a set of fixture files into the database, all within a single transaction.
+///
+/// # Parameters
+/// - `db` - The database to load the fixtures into
+/// - `paths` - The paths to the fixture files, in the order to load them
+///
+/// # Errors
+/// If any of the fixtures couldn't be loaded or applied. If this happens then none of them are applied.
+#[tracing::instrument(name = "database::fixtures::load", skip(db))]
+pub async fn load(db: &Database, paths: &[PathBuf]) -> Result<(), FixtureError> {
+    let fixtures = paths
+        .iter()
+        .map(|path| Fixture::load(path).map(|fixture| (path, fixture)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut conn = db.connect().await;
+    let tx = conn.begin().await;
+
+    for (path, fixture) in &fixtures {
+        tracing::debug!(path = ?path, "Applying fixture");
+        fixture
+            .apply(&tx)
+            .await
+            .map_err(|e| FixtureError::Database((*path).clone(), e))?;
+    }
+
+    tx.commit().await.map_err(FixtureError::Commit)?;
+
+    tracing::info!(count = ?fixtures.len(), "Loaded fixtures");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::{check, let_assert};
+    use serde_json::json;
+
+    #[test]
+    fn from_tables() {
+        let tables = json!({
+            "users": [
+                {"name": "Graham", "age": 40},
+                {"name": "Fred"}
+            ],
+            "posts": [
+                {"title": "Hello"}
+            ]
+        });
+        let_assert!(Value::Object(tables) = tables);
+
+        let_assert!(Ok(Fixture::Rows(rows)) = Fixture::from_tables(tables));
+        check!(rows.len() == 2);
+
+        check!(rows[0].0 == "users");
+        check!(rows[0].1.len() == 2);
+        check!(Value::from(rows[0].1[0].clone()) == json!({"name": "Graham", "age": 40}));
+        check!(Value::from(rows[0].1[1].clone()) == json!({"name": "Fred"}));
+
+        check!(rows[1].0 == "posts");
+        check!(rows[1].1.len() == 1);
+        check!(Value::from(rows[1].1[0].clone()) == json!({"title": "Hello"}));
+    }
+
+    #[test]
+    fn from_tables_not_rows() {
+        let_assert!(Value::Object(tables) = json!({"users": {"name": "Graham"}}));
+
+        let_assert!(Err(e) = Fixture::from_tables(tables));
+        check!(e.starts_with("Invalid rows for table users"));
+    }
+
+    #[test]
+    fn build_insert_statement() {
+        let_assert!(Value::Object(row) = json!({"name": "Graham", "age": 40}));
+
+        check!(
+            insert_statement("users", &row)
+                == r#"INSERT INTO "users"("name", "age") SELECT "name", "age" FROM json_populate_record(NULL::"users", $1)"#
+        );
+    }
+
+    #[test]
+    fn build_insert_statement_with_schema() {
+        let_assert!(Value::Object(row) = json!({"na\"me": "Graham"}));
+
+        check!(
+            insert_statement("audit.users", &row)
+                == r#"INSERT INTO "audit"."users"("na""me") SELECT "na""me" FROM json_populate_record(NULL::"audit"."users", $1)"#
+        );
+    }
+}
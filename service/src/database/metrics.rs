@@ -0,0 +1,273 @@
+use prometheus::{core::Collector, IntCounter, IntCounterVec, IntGauge, Opts, Registry};
+
+/// The names to use for each of the database metrics.
+struct MetricNames {
+    connections_active: &'static str,
+    connections_total: &'static str,
+    transactions_active: &'static str,
+    transactions_total: &'static str,
+}
+
+/// The current metric names, which are registered under the configured namespace.
+const METRIC_NAMES: MetricNames = MetricNames {
+    connections_active: "connections_active",
+    connections_total: "connections_total",
+    transactions_active: "transactions_active",
+    transactions_total: "transactions_total",
+};
+
+/// The names the metrics were originally registered under, without any namespace.
+/// These are deprecated, and only kept so that existing dashboards continue working whilst they
+/// are moved over to the current names.
+const LEGACY_METRIC_NAMES: MetricNames = MetricNames {
+    connections_active: "conections_active",
+    connections_total: "connections",
+    transactions_active: "transactions_active",
+    transactions_total: "transactions_states",
+};
+
+/// A single set of the database metrics.
+struct MetricSet {
+    connection_gauge: IntGauge,
+    transaction_gauge: IntGauge,
+    connection_count: IntCounter,
+    transaction_count: IntCounterVec,
+}
+
+impl MetricSet {
+    /// Create and register a set of the database metrics.
+    ///
+    /// # Parameters
+    /// - `prometheus` - The Prometheus registry to register the metrics with
+    /// - `names` - The names to register the metrics under
+    /// - `namespace` - The namespace to register the metrics under
+    /// - `database` - The name of the database to use as the `database` label on every metric, or
+    ///   `None` to not label them at all
+    fn new(
+        prometheus: &Registry,
+        names: &MetricNames,
+        namespace: &str,
+        database: Option<&str>,
+    ) -> Self {
+        let opts = |name: &str, help: &str| {
+            let opts = Opts::new(name, help).namespace(namespace);
+            match database {
+                Some(database) => opts.const_label("database", database),
+                None => opts,
+            }
+        };
+
+        Self {
+            connection_gauge: register(
+                prometheus,
+                IntGauge::with_opts(opts(
+                    names.connections_active,
+                    "Number of connections checked out",
+                ))
+                .unwrap(),
+            ),
+            transaction_gauge: register(
+                prometheus,
+                IntGauge::with_opts(opts(
+                    names.transactions_active,
+                    "Number of active transactions",
+                ))
+                .unwrap(),
+            ),
+            connection_count: register(
+                prometheus,
+                IntCounter::with_opts(opts(
+                    names.connections_total,
+                    "Number of connections ever issued",
+                ))
+                .unwrap(),
+            ),
+            transaction_count: register(
+                prometheus,
+                IntCounterVec::new(
+                    opts(
+                        names.transactions_total,
+                        "Number of transactions ever issued by status",
+                    ),
+                    &["status"],
+                )
+                .unwrap(),
+            ),
+        }
+    }
+}
+
+/// Register a collector with the Prometheus registry.
+/// Failure to do so is logged but otherwise ignored, since it only means the metric isn't exported.
+fn register<C>(prometheus: &Registry, collector: C) -> C
+where
+    C: Collector + Clone + 'static,
+{
+    if let Err(e) = prometheus.register(Box::new(collector.clone())) {
+        tracing::error!(e = ?e, desc = ?collector.desc(), "Failed to register database metric");
+    }
+
+    collector
+}
+
+/// The metrics for a single database.
+pub struct Metrics {
+    current: MetricSet,
+    legacy: Option<MetricSet>,
+}
+
+impl Metrics {
+    /// Create and register the metrics for a database.
+    ///
+    /// # Parameters
+    /// - `prometheus` - The Prometheus registry to register the metrics with
+    /// - `namespace` - The namespace to register the metrics under
+    /// - `database` - The name of the database, used as the `database` label on every metric
+    /// - `legacy` - Whether to also register the metrics under their deprecated names. These are
+    ///   registered exactly as they originally were, without the `database` label, so only one
+    ///   database can register them
+    pub fn new(prometheus: &Registry, namespace: &str, database: &str, legacy: bool) -> Self {
+        let current = MetricSet::new(prometheus, &METRIC_NAMES, namespace, Some(database));
+        let legacy = if legacy {
+            Some(MetricSet::new(prometheus, &LEGACY_METRIC_NAMES, "", None))
+        } else {
+            None
+        };
+
+        Self { current, legacy }
+    }
+
+    fn each(&self) -> impl Iterator<Item = &MetricSet> {
+        std::iter::once(&self.current).chain(self.legacy.iter())
+    }
+
+    /// Record that a connection has been checked out of the pool.
+    pub fn connection_opened(&self) {
+        for m in self.each() {
+            m.connection_gauge.inc();
+            m.connection_count.inc();
+        }
+    }
+
+    /// Record that a connection has been returned to the pool.
+    pub fn connection_closed(&self) {
+        for m in self.each() {
+            m.connection_gauge.dec();
+        }
+    }
+
+    /// Record that a transaction has started.
+    pub fn transaction_started(&self) {
+        for m in self.each() {
+            m.transaction_gauge.inc();
+            m.transaction_count.with_label_values(&["start"]).inc();
+        }
+    }
+
+    /// Record the outcome of a transaction.
+    ///
+    /// # Parameters
+    /// - `status` - The outcome of the transaction. Either `commit` or `rollback`.
+    pub fn transaction_finished(&self, status: &str) {
+        for m in self.each() {
+            m.transaction_count.with_label_values(&[status]).inc();
+        }
+    }
+
+    /// Record that a transaction is no longer active.
+    pub fn transaction_closed(&self) {
+        for m in self.each() {
+            m.transaction_gauge.dec();
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use assert2::check;
+    use prometheus::proto::MetricFamily;
+
+    fn family<'a>(families: &'a [MetricFamily], name: &str) -> Option<&'a MetricFamily> {
+        families.iter().find(|family| family.get_name() == name)
+    }
+
+    fn database_label(family: &MetricFamily, index: usize) -> &str {
+        family.get_metric()[index]
+            .get_label()
+            .iter()
+            .find(|label| label.get_name() == "database")
+            .unwrap()
+            .get_value()
+    }
+
+    #[test]
+    fn register_multiple_databases() {
+        let prometheus = Registry::new();
+        let primary = Metrics::new(&prometheus, "bigbang", "primary", false);
+        let replica = Metrics::new(&prometheus, "bigbang", "replica", false);
+
+        primary.connection_opened();
+        primary.connection_opened();
+        replica.connection_opened();
+        replica.transaction_started();
+
+        let families = prometheus.gather();
+        check!(families.len() == 4);
+        check!(family(&families, "conections_active").is_none());
+
+        let connections = family(&families, "bigbang_connections_total").unwrap();
+        check!(connections.get_metric().len() == 2);
+        check!(database_label(connections, 0) == "primary");
+        check!(connections.get_metric()[0].get_counter().get_value() == 2.0);
+        check!(database_label(connections, 1) == "replica");
+        check!(connections.get_metric()[1].get_counter().get_value() == 1.0);
+    }
+
+    fn label_names(family: &MetricFamily) -> Vec<Vec<&str>> {
+        family
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .map(prometheus::proto::LabelPair::get_name)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn register_legacy_names() {
+        let prometheus = Registry::new();
+        let primary = Metrics::new(&prometheus, "bigbang", "primary", true);
+
+        primary.connection_opened();
+        primary.connection_opened();
+        primary.connection_closed();
+        primary.transaction_started();
+
+        let families = prometheus.gather();
+        check!(families.len() == 8);
+
+        let active = family(&families, "bigbang_connections_active").unwrap();
+        check!(database_label(active, 0) == "primary");
+        check!(active.get_metric()[0].get_gauge().get_value() == 1.0);
+
+        let legacy_active = family(&families, "conections_active").unwrap();
+        check!(label_names(legacy_active) == vec![Vec::<&str>::new()]);
+        check!(legacy_active.get_metric()[0].get_gauge().get_value() == 1.0);
+
+        let legacy_connections = family(&families, "connections").unwrap();
+        check!(label_names(legacy_connections) == vec![Vec::<&str>::new()]);
+        check!(legacy_connections.get_metric()[0].get_counter().get_value() == 2.0);
+
+        let legacy_transactions = family(&families, "transactions_active").unwrap();
+        check!(label_names(legacy_transactions) == vec![Vec::<&str>::new()]);
+
+        let legacy_states = family(&families, "transactions_states").unwrap();
+        check!(label_names(legacy_states) == vec![vec!["status"]]);
+    }
+}
@@ -1,5 +1,11 @@
+pub mod lock;
+
 use super::{Database, Transaction};
 use rust_embed::RustEmbed;
+use std::{error::Error, time::Duration};
+use tokio_postgres::error::{DbError, ErrorPosition};
+
+pub use lock::LockError;
 
 /// The embedded migrations files to apply
 #[derive(RustEmbed)]
@@ -7,44 +13,183 @@ use rust_embed::RustEmbed;
 struct Migrations;
 
 /// Migrate the database schema to tha latest version
+///
+/// # Parameters
+/// - `db` - The database to migrate
+/// - `lock_timeout` - How long to wait for any other instance that is already migrating the database
+///
+/// # Errors
+/// If another instance held the migrations lock for longer than the timeout
 #[tracing::instrument(name = "database::migrate", skip(db))]
-pub async fn migrate(db: &Database) {
+pub async fn migrate(db: &Database, lock_timeout: Duration) -> Result<(), LockError> {
     tracing::debug!("Migrating database schema");
 
+    let mut lock_conn = db.connect().await;
+    let lock = lock_conn.begin().await;
+    lock::acquire(&lock, lock_timeout).await?;
+
     let mut conn = db.connect().await;
     let tx = conn.begin().await;
 
-    lock_migrations_table(&tx).await;
-    let applied = list_applied_migrations(&tx).await;
+    create_migrations_table(&tx).await;
     let available = list_available_migrations();
+    let pending = list_pending_migrations(&tx, &available).await;
 
-    let mut count: u32 = 0;
-    for migration in &available {
-        if applied.contains(migration) {
-            tracing::debug!(migration = ?migration, "Migration already applied");
-        } else {
-            tracing::debug!(migration = ?migration, "Applying migration");
-            let contents = Migrations::get(migration).expect("Failed to load migration");
-
-            tx.batch_execute(std::str::from_utf8(&contents).expect("Failed to load migration"))
-                .await
-                .expect("Failed to apply migration");
-            tx.execute(
-                "INSERT INTO __migrations(migration_file) VALUES ($1)",
-                &[migration],
-            )
+    for migration in &pending {
+        tracing::debug!(migration = ?migration.file, "Applying migration");
+        apply_migration(&tx, migration)
             .await
-            .expect("Failed to record applied migration");
-            count += 1;
-        }
+            .expect("Failed to apply migration");
     }
 
     tx.commit().await.expect("Failed to commit transaction");
+    lock::release(lock).await;
+
+    tracing::info!(count = ?(pending.len()), total = ?(available.len()), "Applied migrations");
+
+    Ok(())
+}
+
+/// A migration that has not yet been applied to the database.
+pub struct PendingMigration {
+    /// The name of the migration file.
+    pub file: String,
+    /// The SQL contained in the migration file.
+    pub sql: String,
+}
+
+/// Details of a migration that failed to apply.
+#[derive(Debug)]
+pub struct MigrationFailure {
+    /// The name of the migration file that failed.
+    pub file: String,
+    /// The line and column within the migration file that the failure was reported at, if known.
+    pub position: Option<(usize, usize)>,
+    /// The actual error from the database.
+    pub error: tokio_postgres::Error,
+}
+
+impl std::fmt::Display for MigrationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message =
+            db_error(&self.error).map_or_else(|| self.error.to_string(), ToString::to_string);
+
+        match self.position {
+            Some((line, column)) => write!(
+                f,
+                "Migration {} failed at line {line}, column {column}: {message}",
+                self.file
+            ),
+            None => write!(f, "Migration {} failed: {message}", self.file),
+        }
+    }
+}
+
+impl std::error::Error for MigrationFailure {}
+
+/// The outcome of a dry run of the migrations.
+pub struct DryRun {
+    /// The migrations that would be applied, in order.
+    pub pending: Vec<PendingMigration>,
+    /// Whether the migrations would be applied successfully.
+    pub outcome: Result<(), MigrationFailure>,
+}
+
+/// Work out what migrating the database schema would do, without actually changing it.
+/// All of the pending migrations are applied exactly as they would be for real, but in a
+/// transaction that is always rolled back afterwards.
+///
+/// # Parameters
+/// - `db` - The database to dry-run the migrations against
+/// - `lock_timeout` - How long to wait for any other instance that is already migrating the database
+///
+/// # Errors
+/// If another instance held the migrations lock for longer than the timeout
+#[tracing::instrument(name = "database::migrate::dry_run", skip(db))]
+pub async fn dry_run(db: &Database, lock_timeout: Duration) -> Result<DryRun, LockError> {
+    tracing::debug!("Dry-running database migrations");
+
+    let mut lock_conn = db.connect().await;
+    let lock = lock_conn.begin().await;
+    lock::acquire(&lock, lock_timeout).await?;
+
+    let mut conn = db.connect().await;
+    let tx = conn.begin().await;
+
+    create_migrations_table(&tx).await;
+    let available = list_available_migrations();
+    let pending = list_pending_migrations(&tx, &available).await;
+
+    let mut outcome = Ok(());
+    for migration in &pending {
+        tracing::debug!(migration = ?migration.file, "Trying migration");
+        if let Err(error) = apply_migration(&tx, migration).await {
+            outcome = Err(MigrationFailure {
+                file: migration.file.clone(),
+                position: error_position(&migration.sql, &error),
+                error,
+            });
+            break;
+        }
+    }
+
+    tx.rollback()
+        .await
+        .expect("Failed to roll back transaction");
+    lock::release(lock).await;
+
+    Ok(DryRun { pending, outcome })
+}
+
+/// Apply a single migration, and record that it has been applied.
+async fn apply_migration(
+    tx: &Transaction<'_>,
+    migration: &PendingMigration,
+) -> Result<(), tokio_postgres::Error> {
+    tx.batch_execute(migration.sql.as_str()).await?;
+    tx.execute(
+        "INSERT INTO __migrations(migration_file) VALUES ($1)",
+        &[&migration.file],
+    )
+    .await?;
 
-    tracing::info!(count = ?count, total = ?(available.len()), "Applied migrations");
+    Ok(())
 }
 
-async fn lock_migrations_table(tx: &Transaction<'_>) {
+/// Get the details of the error reported by the database itself, if there were any.
+fn db_error(error: &tokio_postgres::Error) -> Option<&DbError> {
+    error.source().and_then(|e| e.downcast_ref::<DbError>())
+}
+
+/// Work out the line and column within some SQL that an error was reported at.
+///
+/// # Parameters
+/// - `sql` - The SQL that was executed
+/// - `error` - The error from executing it
+///
+/// # Returns
+/// The 1-based line and column, or `None` if the error doesn't indicate a position in the SQL
+pub fn error_position(sql: &str, error: &tokio_postgres::Error) -> Option<(usize, usize)> {
+    match db_error(error)?.position()? {
+        ErrorPosition::Original(position) => Some(line_and_column(sql, *position as usize)),
+        ErrorPosition::Internal { .. } => None,
+    }
+}
+
+/// Convert a 1-based character position within some text into a 1-based line and column.
+fn line_and_column(text: &str, position: usize) -> (usize, usize) {
+    let before = text.chars().take(position.saturating_sub(1));
+
+    before.fold((1, 1), |(line, column), c| {
+        if c == '\n' {
+            (line + 1, 1)
+        } else {
+            (line, column + 1)
+        }
+    })
+}
+
+async fn create_migrations_table(tx: &Transaction<'_>) {
     tracing::trace!("Ensuring the migrations table exists");
     tx.execute(
         "CREATE TABLE IF NOT EXISTS __migrations(
@@ -57,11 +202,33 @@ async fn lock_migrations_table(tx: &Transaction<'_>) {
     )
     .await
     .expect("Failed to create __migrations table");
+}
 
-    tracing::trace!("Locking the migrations table");
-    tx.execute("LOCK TABLE __migrations IN EXCLUSIVE MODE", &[])
-        .await
-        .expect("Failed to lock __migrations table");
+async fn list_pending_migrations(
+    tx: &Transaction<'_>,
+    available: &[String],
+) -> Vec<PendingMigration> {
+    let applied = list_applied_migrations(tx).await;
+
+    available
+        .iter()
+        .filter(|migration| {
+            let already_applied = applied.contains(migration);
+            if already_applied {
+                tracing::debug!(migration = ?migration, "Migration already applied");
+            }
+            !already_applied
+        })
+        .map(|migration| {
+            let contents = Migrations::get(migration).expect("Failed to load migration");
+            PendingMigration {
+                file: migration.clone(),
+                sql: std::str::from_utf8(&contents)
+                    .expect("Failed to load migration")
+                    .to_owned(),
+            }
+        })
+        .collect()
 }
 
 async fn list_applied_migrations(tx: &Transaction<'_>) -> Vec<String> {
@@ -87,3 +254,24 @@ fn list_available_migrations() -> Vec<String> {
 
     migrations
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[test]
+    fn position_on_first_line() {
+        check!(line_and_column("SELECT 1;", 8) == (1, 8));
+    }
+
+    #[test]
+    fn position_on_later_line() {
+        check!(line_and_column("SELECT 1;\nSELECT oops;\n", 18) == (2, 8));
+    }
+
+    #[test]
+    fn position_after_multibyte_characters() {
+        check!(line_and_column("-- café\nSELECT oops;", 16) == (2, 8));
+    }
+}
@@ -0,0 +1,209 @@
+use super::super::Transaction;
+use std::time::{Duration, Instant};
+
+/// The key of the advisory lock that is held whilst migrating the database schema.
+/// This is the ASCII for "bigbang!", so that it's unlikely to clash with any other advisory locks.
+pub const MIGRATIONS_LOCK_KEY: i64 = 0x6269_6762_616e_6721;
+
+/// How often to retry acquiring the lock whilst somebody else holds it.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Details of a database session that holds the migrations lock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockHolder {
+    pub pid: i32,
+    pub application_name: Option<String>,
+    pub client_addr: Option<String>,
+    pub state: Option<String>,
+    pub query: Option<String>,
+}
+
+impl std::fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "pid {} (application: {}, client: {}, state: {}, query: {})",
+            self.pid,
+            self.application_name.as_deref().unwrap_or("-"),
+            self.client_addr.as_deref().unwrap_or("-"),
+            self.state.as_deref().unwrap_or("-"),
+            self.query.as_deref().unwrap_or("-"),
+        )
+    }
+}
+
+/// Errors from acquiring the migrations lock.
+#[derive(Debug)]
+pub enum LockError {
+    /// Somebody else held the lock for longer than we were prepared to wait.
+    Timeout {
+        /// How long we waited for the lock.
+        timeout: Duration,
+        /// The sessions that held the lock when we gave up.
+        holders: Vec<LockHolder>,
+    },
+    /// Acquiring the lock failed for some other reason.
+    Database(tokio_postgres::Error),
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout { timeout, holders } => {
+                write!(
+                    f,
+                    "Timed out after {timeout:?} waiting for the migrations lock"
+                )?;
+                if holders.is_empty() {
+                    Ok(())
+                } else {
+                    let holders = holders
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(f, ". Held by {holders}")
+                }
+            }
+            Self::Database(e) => write!(f, "Failed to acquire the migrations lock: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LockError {}
+
+impl From<tokio_postgres::Error> for LockError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+/// Acquire the migrations lock for the duration of the transaction.
+/// If somebody else holds the lock then we keep retrying until either they release it or the
+/// timeout elapses.
+///
+/// The transaction is only used to hold the lock, and must not be the one that the migrations are
+/// applied in. That transaction has to start after the lock is acquired, so that it sees any
+/// migrations applied by whoever held the lock before us.
+/// If the transaction is dropped without being released - for example because a migration
+/// panicked - then it is rolled back, which releases the lock.
+///
+/// # Parameters
+/// - `tx` - The transaction to acquire the lock in
+/// - `timeout` - How long to wait for the lock
+///
+/// # Errors
+/// If the lock couldn't be acquired within the timeout
+pub async fn acquire(tx: &Transaction<'_>, timeout: Duration) -> Result<(), LockError> {
+    tracing::trace!("Acquiring the migrations lock");
+    let started = Instant::now();
+    let mut logged = false;
+
+    loop {
+        let rows = tx
+            .query(
+                "SELECT pg_try_advisory_xact_lock($1)",
+                &[&MIGRATIONS_LOCK_KEY],
+            )
+            .await?;
+        if rows[0].get::<_, bool>(0) {
+            tracing::trace!("Acquired the migrations lock");
+            return Ok(());
+        }
+
+        let elapsed = started.elapsed();
+        if elapsed >= timeout {
+            let holders = list_holders(tx).await?;
+            tracing::error!(timeout = ?timeout, holders = ?holders, "Timed out waiting for the migrations lock");
+            return Err(LockError::Timeout { timeout, holders });
+        }
+
+        if !logged {
+            let holders = list_holders(tx).await?;
+            tracing::warn!(timeout = ?timeout, holders = ?holders, "Waiting for the migrations lock");
+            logged = true;
+        }
+
+        actix_rt::time::delay_for(POLL_INTERVAL.min(timeout.saturating_sub(elapsed))).await;
+    }
+}
+
+/// Release the migrations lock, by finishing the transaction that holds it.
+///
+/// # Parameters
+/// - `tx` - The transaction that the lock was acquired in
+pub async fn release(tx: Transaction<'_>) {
+    tracing::trace!("Releasing the migrations lock");
+    tx.commit()
+        .await
+        .expect("Failed to release the migrations lock");
+}
+
+/// List the database sessions that currently hold the migrations lock.
+async fn list_holders(tx: &Transaction<'_>) -> Result<Vec<LockHolder>, tokio_postgres::Error> {
+    // A bigint advisory lock key is split across the classid and objid columns of pg_locks.
+    let class_id = MIGRATIONS_LOCK_KEY >> 32;
+    let object_id = MIGRATIONS_LOCK_KEY & 0xffff_ffff;
+
+    let holders = tx
+        .query(
+            "SELECT a.pid, a.application_name, a.client_addr::TEXT, a.state, a.query
+            FROM pg_locks l
+            JOIN pg_stat_activity a ON a.pid = l.pid
+            WHERE l.locktype = 'advisory'
+              AND l.granted
+              AND l.classid::BIGINT = $1
+              AND l.objid::BIGINT = $2
+              AND l.objsubid = 1
+              AND l.pid <> pg_backend_pid()
+            ORDER BY a.pid",
+            &[&class_id, &object_id],
+        )
+        .await?
+        .iter()
+        .map(|row| LockHolder {
+            pid: row.get("pid"),
+            application_name: row.get("application_name"),
+            client_addr: row.get("client_addr"),
+            state: row.get("state"),
+            query: row.get("query"),
+        })
+        .collect();
+
+    Ok(holders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[test]
+    fn display_timeout() {
+        let error = LockError::Timeout {
+            timeout: Duration::from_secs(5),
+            holders: vec![LockHolder {
+                pid: 1234,
+                application_name: Some("bigbang".to_owned()),
+                client_addr: None,
+                state: Some("idle in transaction".to_owned()),
+                query: Some("SELECT 1".to_owned()),
+            }],
+        };
+
+        check!(
+            error.to_string()
+                == "Timed out after 5s waiting for the migrations lock. Held by pid 1234 (application: bigbang, client: -, state: idle in transaction, query: SELECT 1)"
+        );
+    }
+
+    #[test]
+    fn display_timeout_without_holders() {
+        let error = LockError::Timeout {
+            timeout: Duration::from_secs(5),
+            holders: vec![],
+        };
+
+        check!(error.to_string() == "Timed out after 5s waiting for the migrations lock");
+    }
+}
@@ -0,0 +1,315 @@
+use super::{quote_identifier, Transaction};
+use crate::http::problem::Problem;
+use actix_http::http::StatusCode;
+use postgres_types::{FromSql, ToSql};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio_postgres::Row;
+
+/// The direction to move through a list of results from a cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+    /// Move to the results after the cursor.
+    #[serde(rename = "f")]
+    Forwards,
+    /// Move to the results before the cursor.
+    #[serde(rename = "b")]
+    Backwards,
+}
+
+/// The order that the results are sorted in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+/// A position in a list of results, identified by the sort key and ID of a single row.
+/// The encoded form of this is opaque to clients, and is used in the `cursor` query parameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor<S, I> {
+    /// The direction to move from the row this cursor identifies.
+    #[serde(rename = "d")]
+    pub direction: Direction,
+    /// The value of the sort column on the row.
+    #[serde(rename = "s")]
+    pub sort: S,
+    /// The value of the ID column on the row.
+    #[serde(rename = "i")]
+    pub id: I,
+}
+
+/// Errors from decoding a cursor.
+#[derive(Debug, PartialEq)]
+pub enum CursorError {
+    /// The cursor was not valid base64.
+    Encoding,
+    /// The cursor did not contain a valid position.
+    Contents,
+}
+
+impl std::fmt::Display for CursorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encoding => write!(f, "Cursor was not correctly encoded"),
+            Self::Contents => write!(f, "Cursor did not contain a valid position"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+impl From<CursorError> for Problem {
+    fn from(e: CursorError) -> Self {
+        Self::typed("invalid-cursor", "Invalid cursor", StatusCode::BAD_REQUEST)
+            .with_detail(e.to_string())
+    }
+}
+
+impl<S, I> Cursor<S, I>
+where
+    S: Serialize + DeserializeOwned,
+    I: Serialize + DeserializeOwned,
+{
+    /// Encode the cursor into the opaque string form that is given to clients.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("Failed to serialize cursor");
+        base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Decode a cursor from the string form that was given to clients.
+    ///
+    /// # Parameters
+    /// - `cursor` - The encoded cursor
+    ///
+    /// # Returns
+    /// The decoded cursor
+    ///
+    /// # Errors
+    /// If the cursor string was not a validly encoded cursor
+    pub fn decode(cursor: &str) -> Result<Self, CursorError> {
+        let json = base64::decode_config(cursor, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| CursorError::Encoding)?;
+        serde_json::from_slice(&json).map_err(|_| CursorError::Contents)
+    }
+}
+
+impl<S, I> std::fmt::Display for Cursor<S, I>
+where
+    S: Serialize + DeserializeOwned,
+    I: Serialize + DeserializeOwned,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+/// Description of a query to paginate using a keyset.
+pub struct KeysetQuery<'a> {
+    /// The query to paginate. This must not have any `ORDER BY` or `LIMIT` clauses of its own.
+    pub sql: &'a str,
+    /// The column that the results are sorted by.
+    pub sort_column: &'a str,
+    /// The column that uniquely identifies each row, used to break ties in the sort column.
+    pub id_column: &'a str,
+    /// The order to sort the results in.
+    pub order: Order,
+    /// The maximum number of rows to return.
+    pub limit: u32,
+}
+
+impl KeysetQuery<'_> {
+    /// Build the SQL for a single page of this query.
+    ///
+    /// # Parameters
+    /// - `direction` - The direction to move from the cursor, if there is one
+    /// - `cursor_param` - The index of the first bind parameter for the cursor values, if there is one
+    fn build(&self, direction: Direction, cursor_param: Option<usize>) -> String {
+        let sort = format!("keyset.{}", quote_identifier(self.sort_column));
+        let id = format!("keyset.{}", quote_identifier(self.id_column));
+
+        // Moving backwards through the list means walking the sort order in reverse.
+        let ascending = (self.order == Order::Ascending) == (direction == Direction::Forwards);
+        let (comparison, order) = if ascending {
+            (">", "ASC")
+        } else {
+            ("<", "DESC")
+        };
+
+        let filter = cursor_param
+            .map(|p| format!(" WHERE ({sort}, {id}) {comparison} (${p}, ${})", p + 1))
+            .unwrap_or_default();
+
+        format!(
+            "SELECT * FROM ({}) AS keyset{filter} ORDER BY {sort} {order}, {id} {order} LIMIT {}",
+            self.sql,
+            u64::from(self.limit) + 1
+        )
+    }
+}
+
+/// A single page of results from a keyset query.
+pub struct Page<S, I> {
+    /// The rows on this page, in sorted order.
+    pub rows: Vec<Row>,
+    /// The cursor for the page before this one, if there is one.
+    pub prev: Option<Cursor<S, I>>,
+    /// The cursor for the page after this one, if there is one.
+    pub next: Option<Cursor<S, I>>,
+}
+
+impl Transaction<'_> {
+    /// Perform a SQL query within the transaction, returning a single page of the results.
+    ///
+    /// # Parameters
+    /// - `query` - The query to perform
+    /// - `cursor` - The cursor to start from. If not provided then the first page is returned
+    /// - `params` - Any bind parameters for the SQL query
+    ///
+    /// # Returns
+    /// The page of results
+    pub async fn query_page<S, I>(
+        &self,
+        query: &KeysetQuery<'_>,
+        cursor: Option<&Cursor<S, I>>,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Page<S, I>, tokio_postgres::Error>
+    where
+        S: ToSql + Sync + for<'r> FromSql<'r>,
+        I: ToSql + Sync + for<'r> FromSql<'r>,
+    {
+        let direction = cursor.map_or(Direction::Forwards, |c| c.direction);
+
+        let mut all_params = params.to_vec();
+        let cursor_param = cursor.map(|c| {
+            all_params.push(&c.sort);
+            all_params.push(&c.id);
+            params.len() + 1
+        });
+
+        let sql = query.build(direction, cursor_param);
+        let mut rows = self.query(sql, &all_params).await?;
+
+        let has_more = rows.len() > query.limit as usize;
+        rows.truncate(query.limit as usize);
+        if direction == Direction::Backwards {
+            rows.reverse();
+        }
+
+        let cursor_for = |row: Option<&Row>, direction| {
+            row.map(|row| Cursor {
+                direction,
+                sort: row.get(query.sort_column),
+                id: row.get(query.id_column),
+            })
+        };
+
+        // There is always more in the direction that we came from, if we came from somewhere.
+        let (more_before, more_after) = match direction {
+            Direction::Forwards => (cursor.is_some(), has_more),
+            Direction::Backwards => (has_more, true),
+        };
+
+        let prev = if more_before {
+            cursor_for(rows.first(), Direction::Backwards)
+        } else {
+            None
+        };
+        let next = if more_after {
+            cursor_for(rows.last(), Direction::Forwards)
+        } else {
+            None
+        };
+
+        Ok(Page { rows, prev, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::{check, let_assert};
+
+    const QUERY: KeysetQuery = KeysetQuery {
+        sql: "SELECT * FROM users WHERE active = $1",
+        sort_column: "name",
+        id_column: "id",
+        order: Order::Ascending,
+        limit: 10,
+    };
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = Cursor {
+            direction: Direction::Backwards,
+            sort: "Graham".to_owned(),
+            id: 42,
+        };
+
+        let encoded = cursor.encode();
+        check!(!encoded.contains('='));
+        check!(encoded == cursor.to_string());
+
+        let_assert!(Ok(decoded) = Cursor::<String, i32>::decode(&encoded));
+        check!(decoded == cursor);
+    }
+
+    #[test]
+    fn decode_invalid_encoding() {
+        let_assert!(Err(e) = Cursor::<String, i32>::decode("not base64!"));
+        check!(e == CursorError::Encoding);
+    }
+
+    #[test]
+    fn decode_invalid_contents() {
+        let encoded = base64::encode_config("[1, 2, 3]", base64::URL_SAFE_NO_PAD);
+
+        let_assert!(Err(e) = Cursor::<String, i32>::decode(&encoded));
+        check!(e == CursorError::Contents);
+    }
+
+    #[test]
+    fn build_first_page() {
+        check!(
+            QUERY.build(Direction::Forwards, None)
+                == r#"SELECT * FROM (SELECT * FROM users WHERE active = $1) AS keyset ORDER BY keyset."name" ASC, keyset."id" ASC LIMIT 11"#
+        );
+    }
+
+    #[test]
+    fn build_forwards() {
+        check!(
+            QUERY.build(Direction::Forwards, Some(2))
+                == r#"SELECT * FROM (SELECT * FROM users WHERE active = $1) AS keyset WHERE (keyset."name", keyset."id") > ($2, $3) ORDER BY keyset."name" ASC, keyset."id" ASC LIMIT 11"#
+        );
+    }
+
+    #[test]
+    fn build_backwards() {
+        check!(
+            QUERY.build(Direction::Backwards, Some(2))
+                == r#"SELECT * FROM (SELECT * FROM users WHERE active = $1) AS keyset WHERE (keyset."name", keyset."id") < ($2, $3) ORDER BY keyset."name" DESC, keyset."id" DESC LIMIT 11"#
+        );
+    }
+
+    #[test]
+    fn build_descending_forwards() {
+        let query = KeysetQuery {
+            order: Order::Descending,
+            ..QUERY
+        };
+
+        check!(
+            query.build(Direction::Forwards, Some(2))
+                == r#"SELECT * FROM (SELECT * FROM users WHERE active = $1) AS keyset WHERE (keyset."name", keyset."id") < ($2, $3) ORDER BY keyset."name" DESC, keyset."id" DESC LIMIT 11"#
+        );
+    }
+
+    #[test]
+    fn problem_from_cursor_error() {
+        let problem = Problem::from(CursorError::Encoding);
+
+        check!(problem.status == StatusCode::BAD_REQUEST);
+        check!(problem.type_uri == "tag:grahamcox.co.uk,2021:bigbang/problems/invalid-cursor");
+        check!(problem.detail == Some("Cursor was not correctly encoded".to_owned()));
+    }
+}
@@ -0,0 +1,104 @@
+use opentelemetry::trace::TraceContextExt;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// The characters to escape in comment values. This matches `encodeURIComponent`, except that
+/// single quotes are also escaped since they delimit the values.
+const VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'(')
+    .remove(b')');
+
+/// Append a sqlcommenter comment to a SQL statement, describing the request and trace that it
+/// is being executed as part of, so that it can be tied back to them from the database side.
+///
+/// # Parameters
+/// - `sql` - The SQL statement
+/// - `span` - The span that the statement is being executed in
+///
+/// # Returns
+/// The SQL statement with the comment appended, or unchanged if there is nothing to describe
+pub fn annotate(sql: String, span: &tracing::Span) -> String {
+    let context = span.context();
+    let span_context = context.span().span_context();
+
+    let traceparent = if span_context.is_valid() {
+        Some(format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id().to_hex(),
+            span_context.span_id().to_hex(),
+            span_context.trace_flags()
+        ))
+    } else {
+        None
+    };
+
+    let route = crate::server::current_route();
+
+    match comment(&[
+        ("route", route.as_deref()),
+        ("traceparent", traceparent.as_deref()),
+    ]) {
+        Some(comment) => format!("{sql} {comment}"),
+        None => sql,
+    }
+}
+
+/// Build a sqlcommenter comment from a set of key/value pairs.
+/// The pairs must already be sorted by key, and any without a value are left out.
+fn comment(values: &[(&str, Option<&str>)]) -> Option<String> {
+    let pairs = values
+        .iter()
+        .filter_map(|(key, value)| {
+            value.map(|value| format!("{key}='{}'", utf8_percent_encode(value, VALUE)))
+        })
+        .collect::<Vec<_>>();
+
+    if pairs.is_empty() {
+        None
+    } else {
+        Some(format!("/*{}*/", pairs.join(",")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[test]
+    fn no_values() {
+        check!(comment(&[("route", None), ("traceparent", None)]) == None);
+    }
+
+    #[test]
+    fn all_values() {
+        check!(
+            comment(&[
+                ("route", Some("/audit/{entity_type}/{entity_id}")),
+                (
+                    "traceparent",
+                    Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+                ),
+            ]) == Some("/*route='%2Faudit%2F%7Bentity_type%7D%2F%7Bentity_id%7D',traceparent='00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01'*/".to_owned())
+        );
+    }
+
+    #[test]
+    fn escape_quotes() {
+        check!(
+            comment(&[("route", Some("/it's")), ("traceparent", None)])
+                == Some("/*route='%2Fit%27s'*/".to_owned())
+        );
+    }
+
+    #[test]
+    fn annotate_without_context() {
+        check!(annotate("SELECT 1".to_owned(), &tracing::Span::none()) == "SELECT 1");
+    }
+}
@@ -0,0 +1,181 @@
+use super::{quote_identifier, quote_table, Transaction};
+use crate::http::problem::Problem;
+use actix_http::http::StatusCode;
+use postgres_types::ToSql;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+/// The name of the column holding the ID of a versioned resource.
+pub const ID_COLUMN: &str = "id";
+/// The name of the column holding the version of a versioned resource.
+/// This is a `UUID NOT NULL` column that gets a new random value every time the resource changes,
+/// so that updates can be rejected if the resource has changed since the caller last saw it.
+pub const VERSION_COLUMN: &str = "version";
+
+/// Errors from updating a versioned resource.
+#[derive(Debug)]
+pub enum UpdateError {
+    /// The resource does not exist.
+    NotFound,
+    /// The resource exists but is no longer at the expected version.
+    Conflict {
+        /// The version that the resource is now at.
+        current: Uuid,
+    },
+    /// The update failed for some other reason.
+    Database(tokio_postgres::Error),
+}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Resource not found"),
+            Self::Conflict { current } => {
+                write!(f, "Resource has been modified. Current version: {current}")
+            }
+            Self::Database(e) => write!(f, "Failed to update resource: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl From<tokio_postgres::Error> for UpdateError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl From<UpdateError> for Problem {
+    fn from(e: UpdateError) -> Self {
+        match e {
+            UpdateError::NotFound => Self::new(StatusCode::NOT_FOUND),
+            UpdateError::Conflict { current } => Self::typed(
+                "version-conflict",
+                "The resource has been modified",
+                StatusCode::PRECONDITION_FAILED,
+            )
+            .with_extension("currentVersion", current.to_string()),
+            UpdateError::Database(e) => e.into(),
+        }
+    }
+}
+
+impl Transaction<'_> {
+    /// Update a single versioned resource, as long as it's still at the expected version.
+    /// The resource is given a brand new version as part of the update.
+    ///
+    /// # Parameters
+    /// - `table` - The table that the resource is stored in
+    /// - `id` - The ID of the resource
+    /// - `version` - The version that the resource is expected to be at
+    /// - `changes` - The columns to update, and the new values for them
+    ///
+    /// # Returns
+    /// The entire row for the resource after it was updated
+    ///
+    /// # Errors
+    /// If the resource doesn't exist, or isn't at the expected version, or the update itself fails
+    pub async fn update_versioned(
+        &self,
+        table: &str,
+        id: &(dyn ToSql + Sync),
+        version: Uuid,
+        changes: &[(&str, &(dyn ToSql + Sync))],
+    ) -> Result<Row, UpdateError> {
+        let new_version = Uuid::new_v4();
+
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![id, &version, &new_version];
+        params.extend(changes.iter().map(|(_, value)| *value));
+
+        let sql = update_statement(table, changes.iter().map(|(column, _)| *column));
+        let mut rows = self.query(sql, &params).await?;
+
+        if let Some(row) = rows.pop() {
+            Ok(row)
+        } else {
+            let current = self
+                .query(
+                    format!(
+                        "SELECT {} FROM {} WHERE {} = $1",
+                        quote_identifier(VERSION_COLUMN),
+                        quote_table(table),
+                        quote_identifier(ID_COLUMN)
+                    ),
+                    &[id],
+                )
+                .await?;
+
+            match current.first() {
+                None => Err(UpdateError::NotFound),
+                Some(row) => Err(UpdateError::Conflict {
+                    current: row.get(VERSION_COLUMN),
+                }),
+            }
+        }
+    }
+}
+
+/// Build the SQL to update a versioned resource.
+/// The ID, expected version and new version are bound as `$1`, `$2` and `$3`, and the values of
+/// the changed columns follow on from there in order.
+fn update_statement<'a, C>(table: &str, columns: C) -> String
+where
+    C: Iterator<Item = &'a str>,
+{
+    let assignments = std::iter::once(format!("{} = $3", quote_identifier(VERSION_COLUMN)))
+        .chain(
+            columns
+                .enumerate()
+                .map(|(i, column)| format!("{} = ${}", quote_identifier(column), i + 4)),
+        )
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "UPDATE {} SET {assignments} WHERE {} = $1 AND {} = $2 RETURNING *",
+        quote_table(table),
+        quote_identifier(ID_COLUMN),
+        quote_identifier(VERSION_COLUMN)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+    use serde_json::json;
+
+    #[test]
+    fn build_update_statement() {
+        check!(
+            update_statement("users", vec!["name", "email"].into_iter())
+                == r#"UPDATE "users" SET "version" = $3, "name" = $4, "email" = $5 WHERE "id" = $1 AND "version" = $2 RETURNING *"#
+        );
+    }
+
+    #[test]
+    fn build_update_statement_for_schema() {
+        check!(
+            update_statement("public.users", vec!["name"].into_iter())
+                == r#"UPDATE "public"."users" SET "version" = $3, "name" = $4 WHERE "id" = $1 AND "version" = $2 RETURNING *"#
+        );
+    }
+
+    #[test]
+    fn problem_from_not_found() {
+        let problem = Problem::from(UpdateError::NotFound);
+
+        check!(problem == Problem::new(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn problem_from_conflict() {
+        let current = Uuid::new_v4();
+        let problem = Problem::from(UpdateError::Conflict { current });
+
+        check!(problem.status == StatusCode::PRECONDITION_FAILED);
+        check!(problem.extensions.get("currentVersion") == Some(&json!(current.to_string())));
+        check!(problem.type_uri == "tag:grahamcox.co.uk,2021:bigbang/problems/version-conflict");
+    }
+}
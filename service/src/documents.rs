@@ -0,0 +1,3 @@
+pub(super) mod component;
+mod http;
+mod model;
@@ -0,0 +1,40 @@
+use crate::database::Database;
+use crate::http::routes::Routes;
+use crate::server::Configurer;
+use actix_web::web::ServiceConfig;
+use std::sync::Arc;
+
+/// Component for reading and editing documents
+pub struct Component {
+    database: Arc<Database>,
+}
+
+/// Builder to build the documents component
+pub struct Builder {
+    database: Arc<Database>,
+}
+
+impl Builder {
+    /// Create a new builder for the documents component, storing documents in the given database.
+    pub fn new(database: Arc<Database>) -> Self {
+        Self { database }
+    }
+
+    /// Build the actual documents component.
+    pub fn build(self) -> Arc<Component> {
+        Arc::new(Component {
+            database: self.database,
+        })
+    }
+}
+
+impl Configurer for Component {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config.data(self.database.clone());
+        super::http::configure_server(config);
+    }
+
+    fn register_routes(&self, routes: &mut Routes) {
+        super::http::register_routes(routes);
+    }
+}
@@ -0,0 +1,86 @@
+use super::model::Document;
+use crate::http::{
+    conditional::version_etag,
+    hal::{HalDocument, Property, PropertyType, Template},
+    routes::{Route, Routes, UnknownRoute},
+    uri_template::TemplateValues,
+    urls::UrlBuilder,
+    SimpleRespondable,
+};
+use actix_http::http::Method;
+use actix_web::web::{get, put, ServiceConfig};
+use serde::Serialize;
+
+mod get;
+mod list;
+mod put;
+
+/// The route for the collection of all documents.
+pub const DOCUMENTS: Route = Route::new("documents", "/documents").with_query(&["offset", "limit"]);
+
+/// The route for a single document.
+pub const DOCUMENT: Route = Route::new("document", "/documents/{id}");
+
+pub fn register_routes(routes: &mut Routes) {
+    routes.register(DOCUMENTS);
+    routes.register(DOCUMENT);
+}
+
+pub fn configure_server(config: &mut ServiceConfig) {
+    config.service(DOCUMENTS.resource().route(get().to(list::handle)));
+    config.service(
+        DOCUMENT
+            .resource()
+            .route(get().to(get::handle))
+            .route(put().to(put::handle)),
+    );
+}
+
+#[derive(Serialize)]
+pub struct DocumentBody {
+    pub id: i32,
+    pub title: String,
+}
+
+/// Build the HAL document for a document, linking to itself.
+fn hal_document(document: Document, urls: &UrlBuilder) -> Result<HalDocument, UnknownRoute> {
+    let self_link = urls.route(
+        &DOCUMENT,
+        &TemplateValues::default().with("id", document.id),
+    )?;
+
+    Ok(HalDocument::new(DocumentBody {
+        id: document.id,
+        title: document.title,
+    })
+    .with_link("self", self_link))
+}
+
+/// Build the representation of a document, tagged with its current version so that clients can
+/// send it back in `If-Match` when they edit the document.
+/// Clients that ask for HAL-FORMS are also told how to edit it.
+fn respondable(
+    document: Document,
+    urls: &UrlBuilder,
+) -> Result<SimpleRespondable<HalDocument>, UnknownRoute> {
+    let etag = version_etag(&document.version);
+    let modified = document.modified.into();
+
+    let edit = Template::new(&Method::PUT)
+        .with_title("Edit document")
+        .with_content_type("application/json")
+        .with_property(
+            Property::new("title")
+                .with_type(PropertyType::Text)
+                .with_prompt("Title")
+                .required()
+                .with_value(&document.title),
+        );
+
+    let hal_document = hal_document(document, urls)?.with_template("default", edit);
+
+    Ok(SimpleRespondable::from(hal_document)
+        .with_etag(etag)
+        .with_last_modified(modified)
+        .with_vary(urls.vary().to_vec()))
+}
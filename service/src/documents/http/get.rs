@@ -0,0 +1,24 @@
+use crate::database::Database;
+use crate::documents::model;
+use crate::http::{
+    hal::HalDocument, problem::Problem, urls::UrlBuilder, Response, SimpleRespondable,
+};
+use actix_http::http::StatusCode;
+use actix_web::web::{Data, Path};
+use std::sync::Arc;
+
+pub async fn handle(
+    path: Path<i32>,
+    database: Data<Arc<Database>>,
+    urls: UrlBuilder,
+) -> Result<Response<SimpleRespondable<HalDocument>>, Problem> {
+    let mut conn = database.connect().await;
+    let tx = conn.begin().await;
+    let document = model::find(&tx, path.into_inner()).await?;
+    tx.commit().await?;
+
+    match document {
+        Some(document) => Ok(super::respondable(document, &urls)?.into()),
+        None => Err(Problem::new(StatusCode::NOT_FOUND)),
+    }
+}
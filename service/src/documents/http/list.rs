@@ -0,0 +1,55 @@
+use super::DOCUMENTS;
+use crate::database::Database;
+use crate::documents::model;
+use crate::http::{
+    hal::{HalCollection, HalDocument},
+    problem::Problem,
+    urls::UrlBuilder,
+    Response, SimpleRespondable,
+};
+use actix_web::web::{Data, Query};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// The number of documents on each page, unless the client asks for a different number.
+const DEFAULT_LIMIT: u64 = 20;
+
+/// The most documents that can be on a single page.
+const MAX_LIMIT: u64 = 100;
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+pub async fn handle(
+    query: Query<PageQuery>,
+    database: Data<Arc<Database>>,
+    urls: UrlBuilder,
+) -> Result<Response<SimpleRespondable<HalDocument>>, Problem> {
+    let offset = query.offset.unwrap_or_default();
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+    let mut conn = database.connect().await;
+    let tx = conn.begin().await;
+    let total = model::count(&tx).await?;
+    let documents = model::list(&tx, offset, limit).await?;
+    tx.commit().await?;
+
+    let items = documents
+        .into_iter()
+        .map(|document| super::hal_document(document, &urls))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let hal_document = HalDocument::from(
+        HalCollection::offset(items, urls.route_template(&DOCUMENTS)?, offset, limit)
+            .with_total(total),
+    )
+    .resolve_links(&urls);
+
+    Ok(SimpleRespondable::from(hal_document)
+        .with_link_header(vec!["self", "first", "prev", "next", "last"])
+        .with_vary(urls.vary().to_vec())
+        .into())
+}
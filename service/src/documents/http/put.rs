@@ -0,0 +1,60 @@
+use crate::database::{
+    audit::{AuditContext, AuditedEntity},
+    Database,
+};
+use crate::documents::model::{self, Document, ENTITY_TYPE, TABLE};
+use crate::http::{
+    conditional::{version_etag, IfMatch},
+    hal::HalDocument,
+    problem::Problem,
+    urls::UrlBuilder,
+    Response, SimpleRespondable,
+};
+use actix_http::http::StatusCode;
+use actix_web::web::{Data, Json, Path};
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct PutBody {
+    pub title: String,
+}
+
+pub async fn handle(
+    path: Path<i32>,
+    body: Json<PutBody>,
+    if_match: IfMatch,
+    context: AuditContext,
+    database: Data<Arc<Database>>,
+    urls: UrlBuilder,
+) -> Result<Response<SimpleRespondable<HalDocument>>, Problem> {
+    let id = path.into_inner();
+
+    let mut conn = database.connect().await;
+    let tx = conn.begin().await;
+
+    let current = model::find(&tx, id)
+        .await?
+        .ok_or_else(|| Problem::new(StatusCode::NOT_FOUND))?;
+    if_match.check(&version_etag(&current.version))?;
+
+    let entity_id = id.to_string();
+    // Anything that changed the document since it was loaded is still caught here.
+    let row = tx
+        .update_versioned_audited(
+            &context,
+            AuditedEntity {
+                entity_type: ENTITY_TYPE,
+                entity_id: &entity_id,
+            },
+            TABLE,
+            &id,
+            current.version,
+            &[("title", &body.title), ("modified", &Utc::now())],
+        )
+        .await?;
+    tx.commit().await?;
+
+    Ok(super::respondable(Document::from(&row), &urls)?.into())
+}
@@ -0,0 +1,95 @@
+use crate::database::Transaction;
+use chrono::{DateTime, Utc};
+use std::convert::TryFrom;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+/// The table that documents are stored in.
+pub const TABLE: &str = "documents";
+
+/// The type that changes to documents are recorded against in the audit log.
+pub const ENTITY_TYPE: &str = "document";
+
+/// A single document, as stored in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub id: i32,
+    pub version: Uuid,
+    pub title: String,
+    pub modified: DateTime<Utc>,
+}
+
+impl From<&Row> for Document {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            version: row.get("version"),
+            title: row.get("title"),
+            modified: row.get("modified"),
+        }
+    }
+}
+
+/// Load a single document.
+///
+/// # Parameters
+/// - `tx` - The transaction to load the document in
+/// - `id` - The ID of the document
+///
+/// # Returns
+/// The document, or `None` if it doesn't exist
+///
+/// # Errors
+/// If the query fails
+pub async fn find(
+    tx: &Transaction<'_>,
+    id: i32,
+) -> Result<Option<Document>, tokio_postgres::Error> {
+    let rows = tx
+        .query(
+            "SELECT id, version, title, modified FROM documents WHERE id = $1",
+            &[&id],
+        )
+        .await?;
+
+    Ok(rows.first().map(Document::from))
+}
+
+/// Load a single page of documents, in order of ID.
+///
+/// # Parameters
+/// - `tx` - The transaction to load the documents in
+/// - `offset` - The number of documents to skip
+/// - `limit` - The maximum number of documents to load
+///
+/// # Errors
+/// If the query fails
+pub async fn list(
+    tx: &Transaction<'_>,
+    offset: u64,
+    limit: u64,
+) -> Result<Vec<Document>, tokio_postgres::Error> {
+    let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+    let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+
+    let rows = tx
+        .query(
+            "SELECT id, version, title, modified FROM documents ORDER BY id OFFSET $1 LIMIT $2",
+            &[&offset, &limit],
+        )
+        .await?;
+
+    Ok(rows.iter().map(Document::from).collect())
+}
+
+/// Count the total number of documents.
+///
+/// # Errors
+/// If the query fails
+pub async fn count(tx: &Transaction<'_>) -> Result<u64, tokio_postgres::Error> {
+    let rows = tx
+        .query("SELECT COUNT(*) AS count FROM documents", &[])
+        .await?;
+
+    Ok(u64::try_from(rows[0].get::<_, i64>("count")).unwrap_or_default())
+}
@@ -1,5 +1,8 @@
 pub(super) mod component;
+mod context;
 mod http;
+mod metrics;
 pub(super) mod service;
 
+pub use context::LinkContext;
 pub use service::*;
@@ -1,7 +1,12 @@
-use super::{HomeLinksUseCase, LinkContributor};
+use super::{metrics::Metrics, HomeLinksUseCase, LinkContributor, RouteLinks};
+use crate::http::{hal::Curie, routes::Routes};
 use crate::server::Configurer;
 use actix_web::web::ServiceConfig;
-use std::sync::Arc;
+use prometheus::Registry;
+use std::{sync::Arc, time::Duration};
+
+/// The default time to wait for each contributor to generate its links.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Component representing the home document
 pub struct Component {
@@ -9,26 +14,75 @@ pub struct Component {
 }
 
 /// Builder to build the home document component
-#[derive(Default)]
 pub struct Builder {
     contributors: Vec<Arc<dyn LinkContributor>>,
+    curies: Vec<Curie>,
+    timeout: Duration,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            contributors: vec![],
+            curies: vec![],
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
 }
 
 impl Builder {
-    /// Add a new contributor of links to the home document.
+    /// Add a new contributor of links to the home document, along with any CURIEs it uses.
     #[allow(dead_code)]
     pub fn with_contributor(mut self, contributor: Arc<dyn LinkContributor>) -> Self {
+        for curie in contributor.curies() {
+            self = self.with_curie(curie);
+        }
         self.contributors.push(contributor);
 
         self
     }
 
+    /// Register a CURIE used to namespace link relations in the home document.
+    ///
+    /// # Panics
+    /// If a different CURIE has already been registered with the same prefix
+    pub fn with_curie(mut self, curie: Curie) -> Self {
+        match self.curies.iter().find(|c| c.name == curie.name) {
+            Some(existing) if existing == &curie => {}
+            Some(existing) => panic!(
+                "CURIE prefix {} is already registered for {}, so can't be used for {}",
+                curie.name, existing.href, curie.href
+            ),
+            None => self.curies.push(curie),
+        }
+
+        self
+    }
+
+    /// Specify how long to wait for each contributor to generate its links before leaving them
+    /// out of the home document.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Build the actual home document component.
-    pub fn build(self) -> Arc<Component> {
+    ///
+    /// # Parameters
+    /// - `prometheus` - The Prometheus registry to register the metrics with
+    pub fn build(self, prometheus: &Registry) -> Arc<Component> {
         let mut contributors = self.contributors;
-        contributors.push(Arc::new(vec![("self".to_owned(), "/".into())]));
+        contributors.push(Arc::new(RouteLinks(vec![(
+            "self".to_owned(),
+            super::http::HOME,
+        )])));
 
-        let service = Arc::new(HomeLinksUseCase { contributors });
+        let service = Arc::new(HomeLinksUseCase {
+            contributors,
+            curies: self.curies,
+            timeout: self.timeout,
+            metrics: Metrics::new(prometheus),
+        });
 
         Arc::new(Component { service })
     }
@@ -39,4 +93,62 @@ impl Configurer for Component {
         config.data(self.service.clone());
         super::http::configure_server(config);
     }
+
+    fn register_routes(&self, routes: &mut Routes) {
+        super::http::register_routes(routes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::home::{LinkContext, LinkContributorError};
+    use crate::http::hal::Link;
+    use assert2::check;
+    use async_trait::async_trait;
+
+    /// Contributor of links that are namespaced with a CURIE.
+    struct CuriedLinks;
+
+    #[async_trait]
+    impl LinkContributor for CuriedLinks {
+        #[allow(clippy::unused_async)]
+        async fn generate_links(
+            &self,
+            _: &LinkContext,
+        ) -> Result<Vec<(String, Link)>, LinkContributorError> {
+            Ok(vec![("bb:audit".to_owned(), "/audit".into())])
+        }
+
+        fn curies(&self) -> Vec<Curie> {
+            vec![Curie::new("bb", "/docs/rels/{rel}")]
+        }
+    }
+
+    #[test]
+    fn contributor_curies() {
+        let component = Builder::default()
+            .with_curie(Curie::new("bb", "/docs/rels/{rel}"))
+            .with_contributor(Arc::new(CuriedLinks))
+            .with_curie(Curie::new("other", "/docs/other/{rel}"))
+            .build(&Registry::new());
+
+        check!(
+            component.service.curies()
+                == [
+                    Curie::new("bb", "/docs/rels/{rel}"),
+                    Curie::new("other", "/docs/other/{rel}")
+                ]
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "CURIE prefix bb is already registered for /docs/rels/{rel}, so can't be used for /other/{rel}"
+    )]
+    fn conflicting_curie() {
+        Builder::default()
+            .with_contributor(Arc::new(CuriedLinks))
+            .with_curie(Curie::new("bb", "/other/{rel}"));
+    }
 }
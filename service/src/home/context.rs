@@ -0,0 +1,171 @@
+use crate::http::{urls::UrlBuilder, CredentialHeaders, Principal};
+use actix_http::{
+    http::header::{q, AcceptLanguage, Header, HeaderName, ACCEPT_LANGUAGE},
+    Payload,
+};
+use actix_web::{FromRequest, HttpRequest};
+use futures::future::{ok, Ready};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The details of the request that the home document is being generated for.
+/// This keeps track of which details the link contributors have looked at, so that the response
+/// can declare what it varies by.
+#[derive(Debug)]
+pub struct LinkContext {
+    principal: Option<Principal>,
+    credentials: CredentialHeaders,
+    urls: UrlBuilder,
+    languages: Vec<String>,
+    used_principal: AtomicBool,
+    used_urls: AtomicBool,
+    used_languages: AtomicBool,
+}
+
+impl LinkContext {
+    /// Create a new context for generating links.
+    ///
+    /// # Parameters
+    /// - `principal` - The authenticated principal making the request, if there is one
+    /// - `credentials` - The request headers that the principal is worked out from
+    /// - `urls` - The URL builder for the request
+    /// - `languages` - The languages that the caller accepts, most preferred first
+    pub fn new(
+        principal: Option<Principal>,
+        credentials: CredentialHeaders,
+        urls: UrlBuilder,
+        languages: Vec<String>,
+    ) -> Self {
+        Self {
+            principal,
+            credentials,
+            urls,
+            languages,
+            used_principal: AtomicBool::new(false),
+            used_urls: AtomicBool::new(false),
+            used_languages: AtomicBool::new(false),
+        }
+    }
+
+    /// The authenticated principal making the request, or `None` for anonymous requests.
+    #[allow(dead_code)]
+    pub fn principal(&self) -> Option<&Principal> {
+        self.used_principal.store(true, Ordering::Relaxed);
+        self.principal.as_ref()
+    }
+
+    /// The URL builder for the request, to resolve links into absolute URLs and to generate links
+    /// to routes.
+    pub fn urls(&self) -> &UrlBuilder {
+        self.used_urls.store(true, Ordering::Relaxed);
+        &self.urls
+    }
+
+    /// The languages that the caller accepts, most preferred first.
+    #[allow(dead_code)]
+    pub fn languages(&self) -> &[String] {
+        self.used_languages.store(true, Ordering::Relaxed);
+        &self.languages
+    }
+
+    /// Whether the generated links depend on who is making the request.
+    pub fn varies_by_principal(&self) -> bool {
+        self.used_principal.load(Ordering::Relaxed)
+    }
+
+    /// The request headers that the generated links depend on.
+    pub fn vary(&self) -> Vec<HeaderName> {
+        let mut vary = vec![];
+
+        if self.used_languages.load(Ordering::Relaxed) {
+            vary.push(ACCEPT_LANGUAGE);
+        }
+        if self.varies_by_principal() {
+            vary.extend(self.credentials.0.iter().cloned());
+        }
+        if self.used_urls.load(Ordering::Relaxed) {
+            vary.extend(self.urls.vary().iter().cloned());
+        }
+
+        vary
+    }
+}
+
+impl FromRequest for LinkContext {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let mut languages = AcceptLanguage::parse(req)
+            .map(|accept| accept.0)
+            .unwrap_or_default();
+        languages.retain(|language| language.quality > q(0));
+        languages.sort_by_key(|language| std::cmp::Reverse(language.quality));
+
+        ok(Self::new(
+            Principal::for_request(req),
+            CredentialHeaders::for_request(req),
+            UrlBuilder::for_request(req),
+            languages
+                .into_iter()
+                .map(|language| language.item.to_string())
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::PrincipalHeader;
+    use actix_http::http::header::{AUTHORIZATION, HOST};
+    use actix_web::test::TestRequest;
+    use assert2::{check, let_assert};
+
+    #[actix_rt::test]
+    async fn anonymous() {
+        let (req, mut payload) = TestRequest::default().to_http_parts();
+
+        let_assert!(Ok(context) = LinkContext::from_request(&req, &mut payload).await);
+        check!(context.principal() == None);
+        check!(context.languages().is_empty());
+        check!(context.urls().base() == "http://localhost:8080");
+        check!(context.vary() == vec![ACCEPT_LANGUAGE, HOST]);
+    }
+
+    #[actix_rt::test]
+    async fn authenticated() {
+        let (req, mut payload) = TestRequest::default()
+            .data(PrincipalHeader(HeaderName::from_static("x-principal")))
+            .header("accept-language", "fr;q=0.5, en-GB, de;q=0")
+            .header("x-principal", "graham")
+            .to_http_parts();
+
+        let_assert!(Ok(context) = LinkContext::from_request(&req, &mut payload).await);
+        check!(context.principal() == Some(&Principal("graham".to_owned())));
+        check!(context.languages() == ["en-GB".to_owned(), "fr".to_owned()]);
+        check!(context.vary() == vec![ACCEPT_LANGUAGE, HeaderName::from_static("x-principal")]);
+    }
+
+    #[test]
+    fn vary() {
+        let context = LinkContext::new(
+            None,
+            CredentialHeaders(vec![AUTHORIZATION]),
+            UrlBuilder::for_request(&TestRequest::default().to_http_request()),
+            vec![],
+        );
+        check!(context.vary().is_empty());
+        check!(!context.varies_by_principal());
+
+        context.languages();
+        check!(context.vary() == vec![ACCEPT_LANGUAGE]);
+
+        context.principal();
+        check!(context.vary() == vec![ACCEPT_LANGUAGE, AUTHORIZATION]);
+        check!(context.varies_by_principal());
+
+        context.urls();
+        check!(context.vary() == vec![ACCEPT_LANGUAGE, AUTHORIZATION, HOST]);
+    }
+}
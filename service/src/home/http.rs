@@ -1,7 +1,15 @@
-use actix_web::web::{get, resource, ServiceConfig};
+use crate::http::routes::{Route, Routes};
+use actix_web::web::{get, ServiceConfig};
 
 mod get;
 
+/// The route for the home document.
+pub const HOME: Route = Route::new("home", "/");
+
+pub fn register_routes(routes: &mut Routes) {
+    routes.register(HOME);
+}
+
 pub fn configure_server(config: &mut ServiceConfig) {
-    config.service(resource("/").route(get().to(get::handle)));
+    config.service(HOME.resource().route(get().to(get::handle)));
 }
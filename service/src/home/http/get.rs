@@ -1,4 +1,4 @@
-use crate::home::HomeLinksUseCase;
+use crate::home::{HomeLinksUseCase, LinkContext};
 use crate::http::{hal::HalDocument, Response, SimpleRespondable};
 use actix_http::http::{
     header::{CacheControl, CacheDirective},
@@ -16,22 +16,34 @@ pub struct HomeDocument {
 
 pub async fn handle(
     home_links: Data<Arc<HomeLinksUseCase>>,
+    context: LinkContext,
 ) -> Response<SimpleRespondable<HalDocument>> {
     let mut hal_document = HalDocument::new(HomeDocument {
         name: env!("CARGO_PKG_NAME"),
         version: env!("CARGO_PKG_VERSION"),
     });
 
-    let links = home_links.generate_links().await;
+    for curie in home_links.curies() {
+        let mut curie = curie.clone();
+        curie.href = context.urls().resolve(&curie.href);
+        hal_document = hal_document.with_curie(curie);
+    }
+
+    let links = home_links.generate_links(&context).await;
     for (name, link) in links {
         hal_document = hal_document.with_link(name, link);
     }
 
+    // Links that depend on who is asking mustn't be stored by shared caches.
+    let visibility = if context.varies_by_principal() {
+        CacheDirective::Private
+    } else {
+        CacheDirective::Public
+    };
+
     SimpleRespondable::from(hal_document)
         .with_status_code(StatusCode::OK)
-        .with_header(CacheControl(vec![
-            CacheDirective::Public,
-            CacheDirective::MaxAge(3600),
-        ]))
+        .with_header(CacheControl(vec![visibility, CacheDirective::MaxAge(3600)]))
+        .with_vary(context.vary())
         .into()
 }
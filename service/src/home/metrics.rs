@@ -0,0 +1,111 @@
+use prometheus::{HistogramOpts, HistogramVec, Registry};
+use std::time::Duration;
+
+/// The namespace to register the home document metrics under.
+const NAMESPACE: &str = "bigbang";
+
+/// The outcome of asking a single contributor for its links.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// The contributor generated its links.
+    Success,
+    /// The contributor returned an error.
+    Failure,
+    /// The contributor didn't finish within the timeout.
+    Timeout,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+/// The metrics for generating the links in the home document.
+pub struct Metrics {
+    contributor_duration: HistogramVec,
+}
+
+impl Metrics {
+    /// Create and register the metrics for the home document.
+    /// Failure to register them is logged but otherwise ignored, since it only means the metrics
+    /// aren't exported.
+    ///
+    /// # Parameters
+    /// - `prometheus` - The Prometheus registry to register the metrics with
+    pub fn new(prometheus: &Registry) -> Self {
+        let contributor_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "home_link_contributor_duration_seconds",
+                "Time taken by each contributor to generate its links for the home document",
+            )
+            .namespace(NAMESPACE),
+            &["contributor", "outcome"],
+        )
+        .unwrap();
+
+        if let Err(e) = prometheus.register(Box::new(contributor_duration.clone())) {
+            tracing::error!(e = ?e, "Failed to register home document metric");
+        }
+
+        Self {
+            contributor_duration,
+        }
+    }
+
+    /// Record how long a contributor took to generate its links.
+    ///
+    /// # Parameters
+    /// - `contributor` - The name of the contributor
+    /// - `outcome` - The outcome of generating the links
+    /// - `duration` - How long it took
+    pub fn contributor_finished(&self, contributor: &str, outcome: Outcome, duration: Duration) {
+        self.contributor_duration
+            .with_label_values(&[contributor, outcome.label()])
+            .observe(duration.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[test]
+    fn record_durations() {
+        let prometheus = Registry::new();
+        let metrics = Metrics::new(&prometheus);
+
+        metrics.contributor_finished("routes", Outcome::Success, Duration::from_millis(5));
+        metrics.contributor_finished("routes", Outcome::Success, Duration::from_millis(7));
+        metrics.contributor_finished("audit", Outcome::Timeout, Duration::from_secs(1));
+
+        let families = prometheus.gather();
+        check!(families.len() == 1);
+        check!(families[0].get_name() == "bigbang_home_link_contributor_duration_seconds");
+
+        let counts = families[0]
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                let labels = metric
+                    .get_label()
+                    .iter()
+                    .map(prometheus::proto::LabelPair::get_value)
+                    .collect::<Vec<_>>();
+                (labels.join("/"), metric.get_histogram().get_sample_count())
+            })
+            .collect::<Vec<_>>();
+        check!(
+            counts
+                == vec![
+                    ("audit/timeout".to_owned(), 1),
+                    ("routes/success".to_owned(), 2)
+                ]
+        );
+    }
+}
@@ -1,36 +1,420 @@
-use crate::http::hal::Link;
+use super::{
+    metrics::{Metrics, Outcome},
+    LinkContext,
+};
+use crate::http::{
+    hal::{Curie, Link},
+    routes::{Route, UnknownRoute},
+    uri_template::TemplateValues,
+};
+use actix_rt::time::{timeout, Instant};
 use async_trait::async_trait;
-use std::sync::Arc;
+use futures::{future::join_all, FutureExt};
+use std::{panic::AssertUnwindSafe, sync::Arc, time::Duration};
+
+/// Error from a contributor failing to generate its links.
+pub type LinkContributorError = Box<dyn std::error::Error + Send + Sync>;
 
 /// Trait for all components that can contribute links to the home document.
 #[async_trait]
 pub trait LinkContributor: Send + Sync {
     /// Generate the links for this component.
-    async fn generate_links(&self) -> Vec<(String, Link)>;
+    /// Any links that the caller isn't able to use should be left out.
+    ///
+    /// # Parameters
+    /// - `context` - The details of the request that the links are being generated for
+    ///
+    /// # Errors
+    /// If the links couldn't be generated. The home document is then returned without them.
+    async fn generate_links(
+        &self,
+        context: &LinkContext,
+    ) -> Result<Vec<(String, Link)>, LinkContributorError>;
+
+    /// The name of this contributor, used when logging and recording metrics.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_owned()
+    }
+
+    /// The CURIEs used to namespace the link relations from this component.
+    fn curies(&self) -> Vec<Curie> {
+        vec![]
+    }
 }
 
 /// Use Case for generating the entire set of links for the home document.
 pub struct HomeLinksUseCase {
     pub(super) contributors: Vec<Arc<dyn LinkContributor>>,
+    pub(super) curies: Vec<Curie>,
+    pub(super) timeout: Duration,
+    pub(super) metrics: Metrics,
 }
 
 impl HomeLinksUseCase {
     /// Generate the links for this component.
-    pub async fn generate_links(&self) -> Vec<(String, Link)> {
-        let mut result = vec![];
+    /// All of the contributors are asked for their links at the same time. Any that fail, panic,
+    /// or don't finish within the timeout, are left out of the result.
+    ///
+    /// # Parameters
+    /// - `context` - The details of the request that the links are being generated for
+    pub async fn generate_links(&self, context: &LinkContext) -> Vec<(String, Link)> {
+        let results = join_all(
+            self.contributors
+                .iter()
+                .map(|c| self.generate_contributor_links(c.as_ref(), context)),
+        )
+        .await;
 
-        for c in &self.contributors {
-            let mut links = c.generate_links().await;
-            result.append(&mut links);
-        }
+        results.into_iter().flatten().collect()
+    }
+
+    /// Generate the links for a single contributor, recording how long it took.
+    ///
+    /// # Returns
+    /// The links from the contributor, or none if it failed, panicked or timed out
+    async fn generate_contributor_links(
+        &self,
+        contributor: &dyn LinkContributor,
+        context: &LinkContext,
+    ) -> Vec<(String, Link)> {
+        let name = contributor.name();
+        let start = Instant::now();
+
+        // A contributor that panics is treated the same as one that fails, so that it can't take
+        // the whole home document down with it.
+        let links = AssertUnwindSafe(contributor.generate_links(context)).catch_unwind();
+        let result = timeout(self.timeout, links).await;
+        let (outcome, links) = match result {
+            Ok(Ok(Ok(links))) => (Outcome::Success, links),
+            Ok(Ok(Err(e))) => {
+                tracing::warn!(e = ?e, contributor = ?name, "Link contributor failed");
+                (Outcome::Failure, vec![])
+            }
+            Ok(Err(_)) => {
+                tracing::warn!(contributor = ?name, "Link contributor panicked");
+                (Outcome::Failure, vec![])
+            }
+            Err(_) => {
+                tracing::warn!(contributor = ?name, "Link contributor timed out");
+                (Outcome::Timeout, vec![])
+            }
+        };
+
+        self.metrics
+            .contributor_finished(&name, outcome, start.elapsed());
 
-        result
+        links
+    }
+
+    /// The CURIEs used to namespace the link relations in the home document.
+    pub fn curies(&self) -> &[Curie] {
+        &self.curies
     }
 }
 
 #[async_trait]
 impl LinkContributor for Vec<(String, Link)> {
-    async fn generate_links(&self) -> Vec<(String, Link)> {
-        self.clone()
+    #[allow(clippy::unused_async)]
+    async fn generate_links(
+        &self,
+        context: &LinkContext,
+    ) -> Result<Vec<(String, Link)>, LinkContributorError> {
+        Ok(self
+            .iter()
+            .map(|(name, link)| (name.clone(), context.urls().resolve_link(link.clone())))
+            .collect())
+    }
+
+    fn name(&self) -> String {
+        "static".to_owned()
+    }
+}
+
+/// Link contributor for links to routes that don't need any values for their paths.
+pub struct RouteLinks(pub Vec<(String, Route)>);
+
+#[async_trait]
+impl LinkContributor for RouteLinks {
+    #[allow(clippy::unused_async)]
+    async fn generate_links(
+        &self,
+        context: &LinkContext,
+    ) -> Result<Vec<(String, Link)>, LinkContributorError> {
+        let urls = context.urls();
+
+        let links = self
+            .0
+            .iter()
+            .map(|(name, route)| {
+                let href = urls.route(route, &TemplateValues::default())?;
+                Ok((name.clone(), href.into()))
+            })
+            .collect::<Result<_, UnknownRoute>>()?;
+
+        Ok(links)
+    }
+
+    fn name(&self) -> String {
+        "routes".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{routes::Routes, urls::UrlBuilder, CredentialHeaders, Principal};
+    use actix_http::http::header::AUTHORIZATION;
+    use actix_rt::time::delay_for;
+    use assert2::check;
+    use prometheus::Registry;
+
+    /// Contributor of a link that only authenticated callers are able to use.
+    struct AuthenticatedLinks;
+
+    #[async_trait]
+    impl LinkContributor for AuthenticatedLinks {
+        #[allow(clippy::unused_async)]
+        async fn generate_links(
+            &self,
+            context: &LinkContext,
+        ) -> Result<Vec<(String, Link)>, LinkContributorError> {
+            match context.principal() {
+                Some(_) => Ok(vec![("profile".to_owned(), "/profile".into())]),
+                None => Ok(vec![]),
+            }
+        }
+    }
+
+    /// Contributor that always fails.
+    struct FailingLinks;
+
+    #[async_trait]
+    impl LinkContributor for FailingLinks {
+        #[allow(clippy::unused_async)]
+        async fn generate_links(
+            &self,
+            _: &LinkContext,
+        ) -> Result<Vec<(String, Link)>, LinkContributorError> {
+            Err("Service unavailable".into())
+        }
+
+        fn name(&self) -> String {
+            "failing".to_owned()
+        }
+    }
+
+    /// Contributor that always panics.
+    struct PanickingLinks;
+
+    #[async_trait]
+    impl LinkContributor for PanickingLinks {
+        #[allow(clippy::unused_async)]
+        async fn generate_links(
+            &self,
+            _: &LinkContext,
+        ) -> Result<Vec<(String, Link)>, LinkContributorError> {
+            panic!("Contributor is broken")
+        }
+
+        fn name(&self) -> String {
+            "panicking".to_owned()
+        }
+    }
+
+    /// Contributor that takes a while to generate its links.
+    struct SlowLinks(Duration);
+
+    #[async_trait]
+    impl LinkContributor for SlowLinks {
+        async fn generate_links(
+            &self,
+            _: &LinkContext,
+        ) -> Result<Vec<(String, Link)>, LinkContributorError> {
+            delay_for(self.0).await;
+            Ok(vec![("slow".to_owned(), "/slow".into())])
+        }
+
+        fn name(&self) -> String {
+            "slow".to_owned()
+        }
+    }
+
+    fn use_case(
+        contributors: Vec<Arc<dyn LinkContributor>>,
+        prometheus: &Registry,
+    ) -> HomeLinksUseCase {
+        let mut all: Vec<Arc<dyn LinkContributor>> =
+            vec![Arc::new(vec![("self".to_owned(), "/".into())])];
+        all.extend(contributors);
+
+        HomeLinksUseCase {
+            contributors: all,
+            curies: vec![],
+            timeout: Duration::from_millis(100),
+            metrics: Metrics::new(prometheus),
+        }
+    }
+
+    fn anonymous() -> LinkContext {
+        LinkContext::new(
+            None,
+            CredentialHeaders(vec![AUTHORIZATION]),
+            UrlBuilder::new("https://example.com"),
+            vec![],
+        )
+    }
+
+    /// Get the number of observations recorded for each contributor and outcome.
+    fn observations(prometheus: &Registry) -> Vec<(String, u64)> {
+        prometheus
+            .gather()
+            .iter()
+            .flat_map(prometheus::proto::MetricFamily::get_metric)
+            .map(|metric| {
+                let labels = metric
+                    .get_label()
+                    .iter()
+                    .map(prometheus::proto::LabelPair::get_value)
+                    .collect::<Vec<_>>();
+                (labels.join("/"), metric.get_histogram().get_sample_count())
+            })
+            .collect()
+    }
+
+    #[actix_rt::test]
+    async fn anonymous_links() {
+        let context = anonymous();
+
+        let links = use_case(vec![Arc::new(AuthenticatedLinks)], &Registry::new())
+            .generate_links(&context)
+            .await;
+
+        check!(links == vec![("self".to_owned(), "https://example.com/".into())]);
+        check!(context.vary() == vec![AUTHORIZATION]);
+    }
+
+    #[actix_rt::test]
+    async fn authenticated_links() {
+        let context = LinkContext::new(
+            Some(Principal("graham".to_owned())),
+            CredentialHeaders(vec![AUTHORIZATION]),
+            UrlBuilder::new("https://example.com"),
+            vec![],
+        );
+
+        let links = use_case(vec![Arc::new(AuthenticatedLinks)], &Registry::new())
+            .generate_links(&context)
+            .await;
+
+        check!(
+            links
+                == vec![
+                    ("self".to_owned(), "https://example.com/".into()),
+                    ("profile".to_owned(), "/profile".into())
+                ]
+        );
+        check!(context.varies_by_principal());
+    }
+
+    #[actix_rt::test]
+    async fn failing_contributor() {
+        let prometheus = Registry::new();
+
+        let links = use_case(vec![Arc::new(FailingLinks)], &prometheus)
+            .generate_links(&anonymous())
+            .await;
+
+        check!(links == vec![("self".to_owned(), "https://example.com/".into())]);
+        check!(
+            observations(&prometheus)
+                == vec![
+                    ("failing/failure".to_owned(), 1),
+                    ("static/success".to_owned(), 1)
+                ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn panicking_contributor() {
+        let prometheus = Registry::new();
+
+        let links = use_case(vec![Arc::new(PanickingLinks)], &prometheus)
+            .generate_links(&anonymous())
+            .await;
+
+        check!(links == vec![("self".to_owned(), "https://example.com/".into())]);
+        check!(
+            observations(&prometheus)
+                == vec![
+                    ("panicking/failure".to_owned(), 1),
+                    ("static/success".to_owned(), 1)
+                ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn slow_contributors() {
+        let prometheus = Registry::new();
+        let use_case = use_case(
+            vec![
+                Arc::new(SlowLinks(Duration::from_millis(60))),
+                Arc::new(SlowLinks(Duration::from_millis(60))),
+                Arc::new(SlowLinks(Duration::from_secs(10))),
+            ],
+            &prometheus,
+        );
+
+        let start = Instant::now();
+        let links = use_case.generate_links(&anonymous()).await;
+
+        check!(start.elapsed() < Duration::from_secs(1));
+        check!(
+            links
+                == vec![
+                    ("self".to_owned(), "https://example.com/".into()),
+                    ("slow".to_owned(), "/slow".into()),
+                    ("slow".to_owned(), "/slow".into())
+                ]
+        );
+        check!(
+            observations(&prometheus)
+                == vec![
+                    ("slow/success".to_owned(), 2),
+                    ("slow/timeout".to_owned(), 1),
+                    ("static/success".to_owned(), 1)
+                ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn route_links() {
+        const HOME: Route = Route::new("home", "/");
+        let mut routes = Routes::default();
+        routes.register(HOME);
+        let context = LinkContext::new(
+            None,
+            CredentialHeaders::default(),
+            UrlBuilder::new("https://example.com").with_routes(Arc::new(routes)),
+            vec![],
+        );
+
+        let known = RouteLinks(vec![("home".to_owned(), HOME)]);
+        let unknown = RouteLinks(vec![
+            ("home".to_owned(), HOME),
+            ("other".to_owned(), Route::new("other", "/other")),
+        ]);
+
+        check!(
+            known.generate_links(&context).await.unwrap()
+                == vec![("home".to_owned(), "https://example.com/".into())]
+        );
+        check!(
+            unknown
+                .generate_links(&context)
+                .await
+                .unwrap_err()
+                .to_string()
+                == "Route other has not been registered"
+        );
     }
 }
@@ -1,12 +1,25 @@
+pub mod conditional;
 pub mod hal;
+pub mod negotiation;
+mod principal;
+pub mod problem;
+pub mod routes;
+pub mod urls;
 
 use actix_http::{
-    http::{header::Header, HeaderMap, StatusCode},
+    http::{
+        header::{ETag, EntityTag, Header, HeaderName, HeaderValue, LastModified, VARY},
+        HeaderMap, StatusCode,
+    },
     Error, Response as HttpResponse,
 };
 use actix_web::Responder;
 use futures::future::{ok, Ready};
 use serde::Serialize;
+use std::time::SystemTime;
+
+pub use bigbang_uri_template as uri_template;
+pub use principal::{CredentialHeaders, Principal, PrincipalHeader};
 
 /// Trait that anything able to represent a response can implement.
 pub trait Respondable {
@@ -79,10 +92,44 @@ where
             Err(_) => {
                 tracing::error!(name = ?name, "Failed to process header");
             }
-        };
+        }
 
         self
     }
+
+    /// Specify the request headers, beyond those used for content negotiation, that the response
+    /// depends on.
+    ///
+    /// # Parameters
+    /// - `headers` - The names of the request headers
+    pub fn with_vary<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        let vary = headers
+            .into_iter()
+            .map(|name| name.as_str().to_owned())
+            .collect::<Vec<_>>();
+
+        if !vary.is_empty() {
+            if let Ok(value) = HeaderValue::from_str(&vary.join(", ")) {
+                self.headers.append(VARY, value);
+            }
+        }
+
+        self
+    }
+
+    /// Specify the entity tag that represents the current version of the resource.
+    /// If this isn't specified then one is computed from the body of the response.
+    pub fn with_etag(self, etag: EntityTag) -> Self {
+        self.with_header(ETag(etag))
+    }
+
+    /// Specify when the resource was last modified
+    pub fn with_last_modified(self, last_modified: SystemTime) -> Self {
+        self.with_header(LastModified(last_modified.into()))
+    }
 }
 
 impl<T> Respondable for SimpleRespondable<T>
@@ -128,15 +175,24 @@ where
     type Error = Error;
     type Future = Ready<Result<HttpResponse, Error>>;
 
-    fn respond_to(self, _req: &actix_web::HttpRequest) -> Self::Future {
-        let mut response = HttpResponse::build(self.0.status_code());
+    fn respond_to(self, req: &actix_web::HttpRequest) -> Self::Future {
+        let response = negotiation::build_negotiated_response(req, self.0);
 
-        for (key, value) in self.0.headers().iter() {
-            response.set_header(key, value.clone());
-        }
+        ok(conditional::evaluate_conditional_get(req, response))
+    }
+}
 
-        let built = response.json(self.0.body());
+/// Build the actual HTTP response for anything that can be responded with.
+fn build_response<R>(respondable: R) -> HttpResponse
+where
+    R: Respondable,
+    R::Body: Serialize,
+{
+    let mut response = HttpResponse::build(respondable.status_code());
 
-        ok(built)
+    for (key, value) in &respondable.headers() {
+        response.set_header(key, value.clone());
     }
+
+    response.json(respondable.body())
 }
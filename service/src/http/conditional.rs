@@ -0,0 +1,402 @@
+use actix_http::{
+    body::{Body, ResponseBody},
+    error::ResponseError,
+    http::{
+        header::{self, EntityTag, Header, HttpDate},
+        Method, StatusCode,
+    },
+    Payload, Response,
+};
+use actix_web::{FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::problem::Problem;
+
+/// The headers from the full response that are also sent on a `304 Not Modified`, as listed in
+/// RFC 7232 section 4.1.
+const NOT_MODIFIED_HEADERS: [header::HeaderName; 6] = [
+    header::CACHE_CONTROL,
+    header::CONTENT_LOCATION,
+    header::DATE,
+    header::ETAG,
+    header::EXPIRES,
+    header::VARY,
+];
+
+/// Build the entity tag that represents a particular version of a resource.
+pub fn version_etag(version: &Uuid) -> EntityTag {
+    EntityTag::strong(version.to_string())
+}
+
+/// Build the strong entity tag that represents a particular representation of a resource, based
+/// on the actual bytes of the representation.
+pub fn content_etag(body: &[u8]) -> EntityTag {
+    EntityTag::strong(base64::encode_config(
+        Sha256::digest(body),
+        base64::URL_SAFE_NO_PAD,
+    ))
+}
+
+/// Evaluate the `If-None-Match` and `If-Modified-Since` headers of a request against the response
+/// that would be sent for it.
+/// Successful `GET` and `HEAD` responses are always given an `ETag`, computed from the body if the
+/// response didn't already have one. If the client's cached copy is still current then the
+/// response is replaced with a `304 Not Modified` that has no body.
+///
+/// # Parameters
+/// - `req` - The request being responded to
+/// - `response` - The response that would be sent for the request
+///
+/// # Returns
+/// The response to actually send
+pub fn evaluate_conditional_get(req: &HttpRequest, mut response: Response) -> Response {
+    if (req.method() != Method::GET && req.method() != Method::HEAD)
+        || response.status() != StatusCode::OK
+    {
+        return response;
+    }
+
+    let etag = match response.headers().get(header::ETAG) {
+        Some(etag) => etag
+            .to_str()
+            .ok()
+            .and_then(|etag| EntityTag::from_str(etag).ok()),
+        None => match response.body() {
+            ResponseBody::Body(Body::Bytes(bytes)) => {
+                let etag = content_etag(bytes);
+                if let Ok(value) = header::HeaderValue::from_str(&etag.to_string()) {
+                    response.headers_mut().insert(header::ETAG, value);
+                }
+                Some(etag)
+            }
+            _ => None,
+        },
+    };
+
+    let not_modified = if req.headers().contains_key(header::IF_NONE_MATCH) {
+        // If-Modified-Since is ignored whenever If-None-Match is present.
+        match (header::IfNoneMatch::parse(req), etag) {
+            (Ok(header::IfNoneMatch::Any), Some(_)) => true,
+            (Ok(header::IfNoneMatch::Items(etags)), Some(etag)) => {
+                etags.iter().any(|e| e.weak_eq(&etag))
+            }
+            _ => false,
+        }
+    } else {
+        let if_modified_since = header::IfModifiedSince::parse(req).ok();
+        let last_modified = response
+            .headers()
+            .get(header::LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| HttpDate::from_str(value).ok());
+
+        match (if_modified_since, last_modified) {
+            (Some(header::IfModifiedSince(since)), Some(last_modified)) => last_modified <= since,
+            _ => false,
+        }
+    };
+
+    if not_modified {
+        let mut not_modified = Response::build(StatusCode::NOT_MODIFIED).finish();
+        for (key, value) in response.headers() {
+            if NOT_MODIFIED_HEADERS.contains(key) {
+                not_modified
+                    .headers_mut()
+                    .append(key.clone(), value.clone());
+            }
+        }
+
+        not_modified
+    } else {
+        response
+    }
+}
+
+/// Error indicating that a conditional request did not match the current state of the resource.
+#[derive(Debug)]
+pub struct PreconditionFailed;
+
+impl std::fmt::Display for PreconditionFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Precondition Failed")
+    }
+}
+
+impl ResponseError for PreconditionFailed {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::PRECONDITION_FAILED
+    }
+
+    fn error_response(&self) -> Response {
+        Problem::from(Self).error_response()
+    }
+}
+
+impl From<PreconditionFailed> for Problem {
+    fn from(_: PreconditionFailed) -> Self {
+        Self::typed(
+            "version-conflict",
+            "The resource has been modified",
+            StatusCode::PRECONDITION_FAILED,
+        )
+    }
+}
+
+/// Extractor for the `If-Match` header, for write endpoints that must only apply their changes if
+/// the resource is still at the version that the client last saw.
+#[derive(Debug)]
+pub struct IfMatch(Option<header::IfMatch>);
+
+impl IfMatch {
+    /// Check the header against the current version of the resource.
+    /// If there was no `If-Match` header at all then the check always passes.
+    ///
+    /// # Parameters
+    /// - `current` - The entity tag for the current version of the resource
+    ///
+    /// # Errors
+    /// If the request doesn't match the current version of the resource
+    pub fn check(&self, current: &EntityTag) -> Result<(), PreconditionFailed> {
+        match &self.0 {
+            None | Some(header::IfMatch::Any) => Ok(()),
+            Some(header::IfMatch::Items(etags)) if etags.iter().any(|e| e.strong_eq(current)) => {
+                Ok(())
+            }
+            Some(header::IfMatch::Items(_)) => Err(PreconditionFailed),
+        }
+    }
+}
+
+impl FromRequest for IfMatch {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let result = if req.headers().contains_key(header::IF_MATCH) {
+            header::IfMatch::parse(req)
+                .map(|h| Self(Some(h)))
+                .map_err(actix_web::Error::from)
+        } else {
+            Ok(Self(None))
+        };
+
+        ready(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use assert2::{check, let_assert};
+
+    async fn extract(req: TestRequest) -> IfMatch {
+        let (req, mut payload) = req.to_http_parts();
+        let_assert!(Ok(if_match) = IfMatch::from_request(&req, &mut payload).await);
+        if_match
+    }
+
+    #[actix_rt::test]
+    async fn no_header() {
+        let if_match = extract(TestRequest::default()).await;
+
+        check!(if_match.check(&EntityTag::strong("abc".to_owned())).is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn any() {
+        let if_match = extract(TestRequest::default().header("if-match", "*")).await;
+
+        check!(if_match.check(&EntityTag::strong("abc".to_owned())).is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn matching() {
+        let if_match = extract(TestRequest::default().header("if-match", r#""def", "abc""#)).await;
+
+        check!(if_match.check(&EntityTag::strong("abc".to_owned())).is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn not_matching() {
+        let if_match = extract(TestRequest::default().header("if-match", r#""def""#)).await;
+
+        let_assert!(Err(e) = if_match.check(&EntityTag::strong("abc".to_owned())));
+        check!(e.status_code() == StatusCode::PRECONDITION_FAILED);
+
+        let response = e.error_response();
+        check!(response.status() == StatusCode::PRECONDITION_FAILED);
+        check!(response.headers().get("content-type").unwrap() == "application/problem+json");
+    }
+
+    #[actix_rt::test]
+    async fn weak_never_matches() {
+        let if_match = extract(TestRequest::default().header("if-match", r#"W/"abc""#)).await;
+
+        check!(if_match
+            .check(&EntityTag::strong("abc".to_owned()))
+            .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn malformed_never_matches() {
+        let if_match = extract(TestRequest::default().header("if-match", "abc")).await;
+
+        check!(if_match
+            .check(&EntityTag::strong("abc".to_owned()))
+            .is_err());
+    }
+
+    fn conditional_get(req: TestRequest, response: Response) -> Response {
+        evaluate_conditional_get(&req.to_http_request(), response)
+    }
+
+    fn ok_response() -> Response {
+        Response::Ok().json(serde_json::json!({ "name": "Test" }))
+    }
+
+    fn etag_of(response: &Response) -> String {
+        response
+            .headers()
+            .get(header::ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn etag_for_content() {
+        check!(
+            content_etag(b"abc").to_string() == r#""ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0""#
+        );
+    }
+
+    #[test]
+    fn computes_etag() {
+        let response = conditional_get(TestRequest::get(), ok_response());
+
+        check!(response.status() == StatusCode::OK);
+        check!(etag_of(&response) == content_etag(br#"{"name":"Test"}"#).to_string());
+    }
+
+    #[test]
+    fn keeps_provided_etag() {
+        let response = Response::Ok()
+            .set(header::ETag(EntityTag::strong("abc".to_owned())))
+            .json(serde_json::json!({}));
+        let response = conditional_get(
+            TestRequest::get().header("if-none-match", r#""abc""#),
+            response,
+        );
+
+        check!(response.status() == StatusCode::NOT_MODIFIED);
+        check!(etag_of(&response) == r#""abc""#);
+    }
+
+    #[test]
+    fn if_none_match_matching() {
+        let etag = etag_of(&conditional_get(TestRequest::get(), ok_response()));
+        let response = Response::Ok()
+            .set(header::CacheControl(vec![header::CacheDirective::MaxAge(
+                60,
+            )]))
+            .header(header::VARY, "Accept")
+            .header(header::LINK, r#"</other>; rel="related""#)
+            .json(serde_json::json!({ "name": "Test" }));
+        let response = conditional_get(
+            TestRequest::get().header("if-none-match", format!(r#""other", W/{etag}"#)),
+            response,
+        );
+
+        check!(response.status() == StatusCode::NOT_MODIFIED);
+        check!(etag_of(&response) == etag);
+        check!(response.headers().get(header::CACHE_CONTROL).unwrap() == "max-age=60");
+        check!(response.headers().get(header::VARY).unwrap() == "Accept");
+        check!(response.headers().get(header::CONTENT_TYPE) == None);
+        check!(response.headers().get(header::LINK) == None);
+        check!(matches!(response.body(), ResponseBody::Body(Body::Empty)));
+    }
+
+    #[test]
+    fn if_none_match_any() {
+        let response = conditional_get(
+            TestRequest::get().header("if-none-match", "*"),
+            ok_response(),
+        );
+
+        check!(response.status() == StatusCode::NOT_MODIFIED);
+    }
+
+    #[test]
+    fn if_none_match_not_matching() {
+        let response = conditional_get(
+            TestRequest::get().header("if-none-match", r#""other""#),
+            ok_response(),
+        );
+
+        check!(response.status() == StatusCode::OK);
+    }
+
+    #[test]
+    fn if_modified_since() {
+        let last_modified = "Tue, 15 Nov 1994 12:45:26 GMT";
+        let response = || {
+            Response::Ok()
+                .header(header::LAST_MODIFIED, last_modified)
+                .json(serde_json::json!({}))
+        };
+
+        let unmodified = conditional_get(
+            TestRequest::get().header("if-modified-since", last_modified),
+            response(),
+        );
+        let modified = conditional_get(
+            TestRequest::get().header("if-modified-since", "Tue, 15 Nov 1994 12:45:25 GMT"),
+            response(),
+        );
+        let ignored = conditional_get(
+            TestRequest::get()
+                .header("if-modified-since", last_modified)
+                .header("if-none-match", r#""other""#),
+            response(),
+        );
+
+        check!(unmodified.status() == StatusCode::NOT_MODIFIED);
+        check!(modified.status() == StatusCode::OK);
+        check!(ignored.status() == StatusCode::OK);
+    }
+
+    #[test]
+    fn only_get_requests() {
+        let response = conditional_get(
+            TestRequest::post().header("if-none-match", "*"),
+            ok_response(),
+        );
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers().get(header::ETAG) == None);
+    }
+
+    #[test]
+    fn only_successful_responses() {
+        let response = conditional_get(
+            TestRequest::get().header("if-none-match", "*"),
+            Response::NotFound().json(serde_json::json!({})),
+        );
+
+        check!(response.status() == StatusCode::NOT_FOUND);
+        check!(response.headers().get(header::ETAG) == None);
+    }
+
+    #[test]
+    fn etag_for_version() {
+        let version = Uuid::parse_str("cd5e4e1a-3f58-4b44-b4d4-bba3a2b70a32").unwrap();
+
+        check!(version_etag(&version).to_string() == r#""cd5e4e1a-3f58-4b44-b4d4-bba3a2b70a32""#);
+    }
+}
@@ -1,5 +1,16 @@
-mod links;
+mod collection;
+mod curie;
 mod document;
+mod forms;
+mod link_header;
+mod links;
+mod resolve;
+mod resource;
 
-pub use links::*;
+pub use collection::*;
+pub use curie::*;
 pub use document::*;
+pub use forms::*;
+pub use link_header::*;
+pub use links::*;
+pub use resource::*;
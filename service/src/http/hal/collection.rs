@@ -0,0 +1,411 @@
+use super::HalDocument;
+use crate::http::{
+    uri_template::{TemplateValues, UriTemplate},
+    SimpleRespondable,
+};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// The relation that the items in a collection are embedded under.
+const ITEM: &str = "item";
+
+/// How a collection is split into pages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paging {
+    /// Pages are selected by the number of items to skip, and the number of items on each page.
+    /// The URI Template of the collection is expanded with `offset` and `limit` variables.
+    Offset { offset: u64, limit: u64 },
+    /// Pages are selected by opaque cursors.
+    /// The URI Template of the collection is expanded with a `cursor` variable.
+    Cursor {
+        /// The cursor for this page, or `None` if this is the first page.
+        current: Option<String>,
+        /// The cursor for the previous page, if there is one.
+        prev: Option<String>,
+        /// The cursor for the next page, if there is one.
+        next: Option<String>,
+    },
+}
+
+/// Representation of a single page of a collection of resources.
+#[derive(Debug, Clone)]
+pub struct HalCollection<T> {
+    /// The items on this page.
+    pub items: Vec<T>,
+    /// The URI Template of the collection, which must have variables for the paging parameters.
+    pub template: UriTemplate,
+    /// The values of any other variables in the URI Template, such as filters.
+    pub values: TemplateValues,
+    /// How the collection is split into pages.
+    pub paging: Paging,
+    /// The total number of items in the collection, if known.
+    pub total: Option<u64>,
+}
+
+/// The metadata describing a single page of a collection.
+#[derive(Debug, Serialize)]
+struct PageMetadata {
+    /// The page number, starting from 1. Only known when paging by offset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<u64>,
+    /// The total number of items in the collection, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<u64>,
+    /// The number of items on this page.
+    count: u64,
+}
+
+impl<T> HalCollection<T> {
+    /// Create a page of a collection that is paged by offset.
+    ///
+    /// # Parameters
+    /// - `items` - The items on this page
+    /// - `template` - The URI Template of the collection, with `offset` and `limit` variables
+    /// - `offset` - The number of items before this page
+    /// - `limit` - The maximum number of items on each page
+    pub fn offset(items: Vec<T>, template: UriTemplate, offset: u64, limit: u64) -> Self {
+        Self {
+            items,
+            template,
+            values: TemplateValues::default(),
+            paging: Paging::Offset { offset, limit },
+            total: None,
+        }
+    }
+
+    /// Create a page of a collection that is paged by cursor.
+    ///
+    /// # Parameters
+    /// - `items` - The items on this page
+    /// - `template` - The URI Template of the collection, with a `cursor` variable
+    /// - `current` - The cursor for this page, or `None` if this is the first page
+    /// - `prev` - The cursor for the previous page, if there is one
+    /// - `next` - The cursor for the next page, if there is one
+    pub fn cursor<C>(
+        items: Vec<T>,
+        template: UriTemplate,
+        current: Option<&C>,
+        prev: Option<&C>,
+        next: Option<&C>,
+    ) -> Self
+    where
+        C: Display,
+    {
+        Self {
+            items,
+            template,
+            values: TemplateValues::default(),
+            paging: Paging::Cursor {
+                current: current.map(ToString::to_string),
+                prev: prev.map(ToString::to_string),
+                next: next.map(ToString::to_string),
+            },
+            total: None,
+        }
+    }
+
+    /// Specify the total number of items in the collection.
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Specify the values of any other variables in the URI Template.
+    pub fn with_values(mut self, values: TemplateValues) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// Build the URI of a single page of the collection, selected by offset.
+    fn offset_link(&self, offset: u64, limit: u64) -> String {
+        self.template.expand(
+            &self
+                .values
+                .clone()
+                .with("offset", offset)
+                .with("limit", limit),
+        )
+    }
+
+    /// Build the URI of a single page of the collection, selected by cursor.
+    fn cursor_link(&self, cursor: Option<&String>) -> String {
+        self.template.expand(
+            &self
+                .values
+                .clone()
+                .with_optional("cursor", cursor.map(String::as_str)),
+        )
+    }
+
+    /// Generate the links for moving between the pages of the collection.
+    fn links(&self) -> Vec<(&'static str, String)> {
+        let mut links = vec![];
+
+        match &self.paging {
+            Paging::Offset { offset, limit } => {
+                let (offset, limit) = (*offset, *limit);
+
+                links.push(("self", self.offset_link(offset, limit)));
+                links.push(("first", self.offset_link(0, limit)));
+
+                // Pages of no items can't be moved between, since every page would be the same one.
+                if offset > 0 && limit > 0 {
+                    links.push((
+                        "prev",
+                        self.offset_link(offset.saturating_sub(limit), limit),
+                    ));
+                }
+
+                let next = offset.saturating_add(limit);
+                let has_next = limit > 0
+                    && match self.total {
+                        Some(total) => next < total,
+                        None => self.items.len() as u64 >= limit,
+                    };
+                if has_next {
+                    links.push(("next", self.offset_link(next, limit)));
+                }
+
+                if let Some(total) = self.total {
+                    let last = if total == 0 || limit == 0 {
+                        0
+                    } else {
+                        (total - 1) / limit * limit
+                    };
+                    links.push(("last", self.offset_link(last, limit)));
+                }
+            }
+            Paging::Cursor {
+                current,
+                prev,
+                next,
+            } => {
+                links.push(("self", self.cursor_link(current.as_ref())));
+                links.push(("first", self.cursor_link(None)));
+
+                if let Some(prev) = prev {
+                    links.push(("prev", self.cursor_link(Some(prev))));
+                }
+                if let Some(next) = next {
+                    links.push(("next", self.cursor_link(Some(next))));
+                }
+            }
+        }
+
+        links
+    }
+
+    /// Build the metadata describing this page of the collection.
+    fn metadata(&self) -> PageMetadata {
+        let page = match &self.paging {
+            Paging::Offset { offset, limit } if *limit > 0 => Some(offset / limit + 1),
+            Paging::Offset { .. } => Some(1),
+            Paging::Cursor { .. } => None,
+        };
+
+        PageMetadata {
+            page,
+            total: self.total,
+            count: self.items.len() as u64,
+        }
+    }
+}
+
+impl<T> From<HalCollection<T>> for HalDocument
+where
+    T: Into<HalDocument>,
+{
+    fn from(collection: HalCollection<T>) -> Self {
+        let mut document = HalDocument::new(collection.metadata());
+
+        for (name, link) in collection.links() {
+            document = document.with_link(name, link);
+        }
+
+        document.with_embedded_list(ITEM, collection.items.into_iter().map(Into::into))
+    }
+}
+
+impl<T> From<HalCollection<T>> for SimpleRespondable<HalDocument>
+where
+    T: Into<HalDocument>,
+{
+    fn from(collection: HalCollection<T>) -> Self {
+        HalDocument::from(collection).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Respondable;
+    use assert2::{check, let_assert};
+    use serde_json::{json, Value};
+
+    fn template() -> UriTemplate {
+        UriTemplate::parse("/users{?name,offset,limit,cursor}").unwrap()
+    }
+
+    /// An item in a collection, which is rendered as a document with no links.
+    struct Item(Value);
+
+    impl From<Item> for HalDocument {
+        fn from(item: Item) -> Self {
+            HalDocument::new(item.0)
+        }
+    }
+
+    fn items(count: u64) -> Vec<Item> {
+        (0..count).map(|id| Item(json!({ "id": id }))).collect()
+    }
+
+    fn render<T>(collection: HalCollection<T>) -> Value
+    where
+        T: Into<HalDocument>,
+    {
+        serde_json::to_value(HalDocument::from(collection)).unwrap()
+    }
+
+    #[test]
+    fn offset_first_page() {
+        let collection = HalCollection::offset(items(2), template(), 0, 2)
+            .with_total(5)
+            .with_values(TemplateValues::default().with("name", "Graham"));
+
+        check!(
+            render(collection)
+                == json!({
+                    "page": 1,
+                    "total": 5,
+                    "count": 2,
+                    "_links": {
+                        "first": { "href": "/users?name=Graham&offset=0&limit=2" },
+                        "last": { "href": "/users?name=Graham&offset=4&limit=2" },
+                        "next": { "href": "/users?name=Graham&offset=2&limit=2" },
+                        "self": { "href": "/users?name=Graham&offset=0&limit=2" }
+                    },
+                    "_embedded": {
+                        "item": [{ "id": 0 }, { "id": 1 }]
+                    }
+                })
+        );
+    }
+
+    #[test]
+    fn offset_middle_page() {
+        let document = render(HalCollection::offset(items(2), template(), 2, 2).with_total(5));
+
+        check!(document["page"] == 2);
+        check!(document["_links"]["prev"]["href"] == "/users?offset=0&limit=2");
+        check!(document["_links"]["next"]["href"] == "/users?offset=4&limit=2");
+    }
+
+    #[test]
+    fn offset_last_page() {
+        let document = render(HalCollection::offset(items(1), template(), 4, 2).with_total(5));
+
+        check!(document["page"] == 3);
+        check!(document["count"] == 1);
+        check!(document["_links"]["prev"]["href"] == "/users?offset=2&limit=2");
+        check!(document["_links"].get("next") == None);
+        check!(document["_links"]["last"]["href"] == "/users?offset=4&limit=2");
+    }
+
+    #[test]
+    fn offset_without_total() {
+        let full = render(HalCollection::offset(items(2), template(), 0, 2));
+        let partial = render(HalCollection::offset(items(1), template(), 2, 2));
+
+        check!(full.get("total") == None);
+        check!(full["_links"]["next"]["href"] == "/users?offset=2&limit=2");
+        check!(full["_links"].get("last") == None);
+        check!(partial["_links"].get("next") == None);
+    }
+
+    #[test]
+    fn offset_overflow() {
+        let document = render(HalCollection::offset(items(2), template(), u64::MAX - 1, 2));
+
+        check!(document["_links"]["next"]["href"] == format!("/users?offset={}&limit=2", u64::MAX));
+    }
+
+    #[test]
+    fn offset_zero_limit() {
+        let document =
+            render(HalCollection::<Item>::offset(vec![], template(), 4, 0).with_total(10));
+
+        check!(document["_links"]["self"]["href"] == "/users?offset=4&limit=0");
+        check!(document["_links"].get("prev") == None);
+        check!(document["_links"].get("next") == None);
+
+        let without_total = render(HalCollection::<Item>::offset(vec![], template(), 0, 0));
+        check!(without_total["_links"].get("next") == None);
+    }
+
+    #[test]
+    fn offset_empty() {
+        let document =
+            render(HalCollection::<Item>::offset(vec![], template(), 0, 2).with_total(0));
+
+        check!(document["count"] == 0);
+        check!(document["_embedded"]["item"] == json!([]));
+        check!(document["_links"]["last"]["href"] == "/users?offset=0&limit=2");
+        check!(document["_links"].get("next") == None);
+    }
+
+    #[test]
+    fn cursor_paging() {
+        let collection = HalCollection::cursor(
+            items(2),
+            template(),
+            Some(&"abc"),
+            Some(&"def"),
+            Some(&"ghi"),
+        )
+        .with_total(10);
+
+        check!(
+            render(collection)
+                == json!({
+                    "total": 10,
+                    "count": 2,
+                    "_links": {
+                        "first": { "href": "/users" },
+                        "next": { "href": "/users?cursor=ghi" },
+                        "prev": { "href": "/users?cursor=def" },
+                        "self": { "href": "/users?cursor=abc" }
+                    },
+                    "_embedded": {
+                        "item": [{ "id": 0 }, { "id": 1 }]
+                    }
+                })
+        );
+    }
+
+    #[test]
+    fn cursor_first_page() {
+        let document = render(HalCollection::cursor::<&str>(
+            items(1),
+            template(),
+            None,
+            None,
+            None,
+        ));
+
+        check!(document.get("page") == None);
+        check!(document["_links"]["self"]["href"] == "/users");
+        check!(document["_links"].get("prev") == None);
+        check!(document["_links"].get("next") == None);
+    }
+
+    #[test]
+    fn respondable() {
+        let respondable =
+            SimpleRespondable::from(HalCollection::offset(items(1), template(), 0, 2));
+
+        let headers = respondable.headers();
+        let_assert!(Some(content_type) = headers.get("content-type"));
+        check!(content_type == "application/hal+json");
+        check!(respondable.body().embedded.contains_key("item"));
+    }
+}
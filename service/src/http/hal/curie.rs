@@ -0,0 +1,109 @@
+use super::{HalDocument, Link, Links};
+
+/// The name of the link relation that CURIEs are listed under.
+pub const CURIES: &str = "curies";
+
+/// Representation of a CURIE, allowing link relations to be namespaced with a short prefix.
+/// A link relation of `prefix:rel` is documented at the CURIE href, with `{rel}` replaced by `rel`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curie {
+    /// The prefix used in link relations.
+    pub name: String,
+    /// The URI Template of the documentation for link relations using this prefix.
+    pub href: String,
+}
+
+impl Curie {
+    /// Create a new CURIE.
+    ///
+    /// # Parameters
+    /// - `name` - The prefix used in link relations
+    /// - `href` - The URI Template of the documentation, containing a `{rel}` variable
+    #[allow(dead_code)]
+    pub fn new<N, H>(name: N, href: H) -> Self
+    where
+        N: Into<String>,
+        H: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            href: href.into(),
+        }
+    }
+}
+
+impl From<Curie> for Link {
+    fn from(curie: Curie) -> Self {
+        Self {
+            href: curie.href,
+            templated: true,
+            name: Some(curie.name),
+            ..Self::default()
+        }
+    }
+}
+
+impl HalDocument {
+    /// Register a CURIE for the link relations in this resource.
+    /// CURIEs are always represented as an array, even if there is only one of them.
+    ///
+    /// # Parameters
+    /// - `curie` - The CURIE to register
+    pub fn with_curie(mut self, curie: Curie) -> Self {
+        let link = Link::from(curie);
+
+        let curies = match self.links.remove(CURIES) {
+            None => Links::Multiple(vec![link]),
+            Some(links) => links.append(link),
+        };
+
+        self.links.insert(CURIES.to_owned(), curies);
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+    use serde_json::json;
+
+    #[test]
+    fn single_curie() {
+        let document = HalDocument::new(json!({}))
+            .with_curie(Curie::new("bb", "/docs/rels/{rel}"))
+            .with_link("bb:audit", "/audit");
+
+        check!(
+            serde_json::to_value(document).unwrap()
+                == json!({
+                    "_links": {
+                        "curies": [
+                            { "href": "/docs/rels/{rel}", "templated": true, "name": "bb" }
+                        ],
+                        "bb:audit": { "href": "/audit" }
+                    }
+                })
+        );
+    }
+
+    #[test]
+    fn multiple_curies() {
+        let document = HalDocument::new(json!({}))
+            .with_curie(Curie::new("bb", "/docs/rels/{rel}"))
+            .with_curie(Curie::new("acme", "https://acme.example/rels/{rel}"));
+
+        check!(
+            serde_json::to_value(document).unwrap()
+                == json!({
+                    "_links": {
+                        "curies": [
+                            { "href": "/docs/rels/{rel}", "templated": true, "name": "bb" },
+                            { "href": "https://acme.example/rels/{rel}", "templated": true, "name": "acme" }
+                        ]
+                    }
+                })
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Link, Links};
+use super::{Link, Links, Template};
 use crate::http::SimpleRespondable;
 use actix_http::http::header::ContentType;
 use serde::Serialize;
@@ -15,6 +15,40 @@ pub struct HalDocument {
     /// The links for the resource
     #[serde(rename = "_links", skip_serializing_if = "BTreeMap::is_empty")]
     pub links: BTreeMap<String, Links>,
+
+    /// The resources embedded in this resource
+    #[serde(rename = "_embedded", skip_serializing_if = "BTreeMap::is_empty")]
+    pub embedded: BTreeMap<String, Embedded>,
+
+    /// The HAL-FORMS templates describing the actions on the resource
+    #[serde(rename = "_templates", skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, Template>,
+}
+
+/// Representation of the set of embedded resources for a single name.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Embedded {
+    /// A single embedded resource.
+    Single(Box<HalDocument>),
+    /// A set of multiple embedded resources.
+    Multiple(Vec<HalDocument>),
+}
+
+impl Embedded {
+    /// Append an additional resource to this representation.
+    /// The result will always be multiple resources, even if the input was only a single one.
+    pub fn append(self, document: HalDocument) -> Self {
+        let documents = match self {
+            Embedded::Single(previous) => vec![*previous, document],
+            Embedded::Multiple(mut previous) => {
+                previous.push(document);
+                previous
+            }
+        };
+
+        Embedded::Multiple(documents)
+    }
 }
 
 impl HalDocument {
@@ -28,6 +62,8 @@ impl HalDocument {
         Self {
             data,
             links: BTreeMap::new(),
+            embedded: BTreeMap::new(),
+            templates: BTreeMap::new(),
         }
     }
 
@@ -53,6 +89,53 @@ impl HalDocument {
 
         self
     }
+
+    /// Embed a resource in this one
+    ///
+    /// # Parameters
+    /// - `name` - The relation of the embedded resource to this one
+    /// - `document` - The actual resource to embed
+    #[allow(dead_code)]
+    pub fn with_embedded<N>(mut self, name: N, document: HalDocument) -> Self
+    where
+        N: Into<String>,
+    {
+        let name = name.into();
+
+        let embedded = match self.embedded.remove(&name) {
+            None => Embedded::Single(Box::new(document)),
+            Some(embedded) => embedded.append(document),
+        };
+
+        self.embedded.insert(name, embedded);
+
+        self
+    }
+
+    /// Embed a list of resources in this one.
+    /// These are always represented as an array, even if there are less than two of them.
+    ///
+    /// # Parameters
+    /// - `name` - The relation of the embedded resources to this one
+    /// - `documents` - The actual resources to embed
+    pub fn with_embedded_list<N, I>(mut self, name: N, documents: I) -> Self
+    where
+        N: Into<String>,
+        I: IntoIterator<Item = HalDocument>,
+    {
+        let name = name.into();
+
+        let mut embedded = match self.embedded.remove(&name) {
+            None => vec![],
+            Some(Embedded::Single(previous)) => vec![*previous],
+            Some(Embedded::Multiple(previous)) => previous,
+        };
+        embedded.extend(documents);
+
+        self.embedded.insert(name, Embedded::Multiple(embedded));
+
+        self
+    }
 }
 
 impl From<HalDocument> for SimpleRespondable<HalDocument> {
@@ -85,6 +168,7 @@ mod tests {
 
         check!(document.data == json!({"name": "Graham"}));
         check!(document.links.is_empty());
+        check!(document.embedded.is_empty());
     }
 
     #[test]
@@ -120,6 +204,96 @@ mod tests {
         check!(links == &vec![Link::from("/foo"), Link::from("/bar")]);
     }
 
+    #[test]
+    fn with_single_embedded() {
+        let author = HalDocument::new(Body {
+            name: "Graham".to_owned(),
+        })
+        .with_link("self", "/users/abc");
+        let document =
+            HalDocument::new(json!({"title": "Hello"})).with_embedded("author", author.clone());
+
+        check!(document.embedded.len() == 1);
+
+        let_assert!(Some(Embedded::Single(embedded)) = document.embedded.get("author"));
+        check!(embedded.as_ref() == &author);
+    }
+
+    #[test]
+    fn with_repeated_embedded() {
+        let first = HalDocument::new(json!({"id": 1}));
+        let second = HalDocument::new(json!({"id": 2}));
+        let document = HalDocument::new(json!({}))
+            .with_embedded("item", first.clone())
+            .with_embedded("item", second.clone());
+
+        let_assert!(Some(Embedded::Multiple(embedded)) = document.embedded.get("item"));
+        check!(embedded == &vec![first, second]);
+    }
+
+    #[test]
+    fn with_embedded_list() {
+        let first = HalDocument::new(json!({"id": 1}));
+        let second = HalDocument::new(json!({"id": 2}));
+        let document = HalDocument::new(json!({}))
+            .with_embedded_list("item", vec![first.clone()])
+            .with_embedded_list("item", vec![second.clone()])
+            .with_embedded_list("other", vec![]);
+
+        let_assert!(Some(Embedded::Multiple(embedded)) = document.embedded.get("item"));
+        check!(embedded == &vec![first, second]);
+
+        let_assert!(Some(Embedded::Multiple(other)) = document.embedded.get("other"));
+        check!(other.is_empty());
+    }
+
+    #[test]
+    fn serialize_embedded() {
+        let document = HalDocument::new(json!({"title": "Hello"}))
+            .with_link("self", "/posts/1")
+            .with_embedded(
+                "author",
+                HalDocument::new(json!({"name": "Graham"})).with_link("self", "/users/abc"),
+            )
+            .with_embedded_list(
+                "comments",
+                vec![HalDocument::new(json!({"text": "Hi"})).with_link("self", "/comments/1")],
+            );
+
+        check!(
+            serde_json::to_value(document).unwrap()
+                == json!({
+                    "title": "Hello",
+                    "_links": {
+                        "self": { "href": "/posts/1" }
+                    },
+                    "_embedded": {
+                        "author": {
+                            "name": "Graham",
+                            "_links": {
+                                "self": { "href": "/users/abc" }
+                            }
+                        },
+                        "comments": [
+                            {
+                                "text": "Hi",
+                                "_links": {
+                                    "self": { "href": "/comments/1" }
+                                }
+                            }
+                        ]
+                    }
+                })
+        );
+    }
+
+    #[test]
+    fn serialize_without_embedded() {
+        let document = HalDocument::new(json!({"title": "Hello"}));
+
+        check!(serde_json::to_value(document).unwrap() == json!({"title": "Hello"}));
+    }
+
     #[test]
     fn to_respondable() {
         let document = HalDocument::new(Body {
@@ -0,0 +1,472 @@
+use super::{HalDocument, Link};
+use actix_http::http::Method;
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+/// Representation of a HAL-FORMS template, describing an action that can be performed on a resource.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    /// The human-readable title of the template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The HTTP method to use when submitting the template.
+    pub method: String,
+    /// The URI to submit the template to. If not present then the URI of the resource is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// The media type to use for the body when submitting the template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// The properties that make up the body when submitting the template.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<Property>,
+}
+
+/// The type of value that a property in a template holds.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[allow(dead_code)]
+pub enum PropertyType {
+    Hidden,
+    Text,
+    Textarea,
+    Search,
+    Tel,
+    Url,
+    Email,
+    Password,
+    Date,
+    Month,
+    Week,
+    Time,
+    DatetimeLocal,
+    Number,
+    Range,
+    Color,
+}
+
+/// Representation of a single property in a HAL-FORMS template.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Property {
+    /// The name of the property.
+    pub name: String,
+    /// The type of value that the property holds.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<PropertyType>,
+    /// The human-readable prompt for the property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Whether a value must be provided for the property.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    /// Whether the value of the property can't be changed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// The initial value of the property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    /// A regular expression that the value must match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    /// The minimum numeric value of the property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<Number>,
+    /// The maximum numeric value of the property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<Number>,
+    /// The minimum length of the value of the property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u64>,
+    /// The maximum length of the value of the property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u64>,
+    /// The set of values that the property can take.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Options>,
+}
+
+/// Representation of the set of values that a property in a HAL-FORMS template can take.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Options {
+    /// The values listed directly in the template.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inline: Vec<OptionValue>,
+    /// A link to a resource that lists the values.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<Link>,
+    /// The minimum number of values that must be selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    /// The maximum number of values that can be selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    /// The values that are initially selected.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub selected_values: Vec<String>,
+}
+
+/// A single value that a property in a HAL-FORMS template can take.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum OptionValue {
+    /// A value that is also used as its own prompt.
+    Simple(String),
+    /// A value with a separate human-readable prompt.
+    #[allow(dead_code)]
+    Prompted { prompt: String, value: String },
+}
+
+impl From<&str> for OptionValue {
+    fn from(value: &str) -> Self {
+        Self::Simple(value.to_owned())
+    }
+}
+
+impl Template {
+    /// Create a new template that is submitted with the given HTTP method.
+    pub fn new(method: &Method) -> Self {
+        Self {
+            title: None,
+            method: method.as_str().to_owned(),
+            target: None,
+            content_type: None,
+            properties: vec![],
+        }
+    }
+
+    /// Specify the human-readable title of the template
+    pub fn with_title<S>(mut self, title: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Specify the URI to submit the template to
+    #[allow(dead_code)]
+    pub fn with_target<S>(mut self, target: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Specify the media type to use for the body when submitting the template
+    pub fn with_content_type<S>(mut self, content_type: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Add a property to the template
+    pub fn with_property(mut self, property: Property) -> Self {
+        self.properties.push(property);
+        self
+    }
+}
+
+impl Property {
+    /// Create a new property with the given name.
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            value_type: None,
+            prompt: None,
+            required: false,
+            read_only: false,
+            value: None,
+            regex: None,
+            min: None,
+            max: None,
+            min_length: None,
+            max_length: None,
+            options: None,
+        }
+    }
+
+    /// Specify the type of value that the property holds
+    pub fn with_type(mut self, value_type: PropertyType) -> Self {
+        self.value_type = Some(value_type);
+        self
+    }
+
+    /// Specify the human-readable prompt for the property
+    pub fn with_prompt<S>(mut self, prompt: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    /// Mark the property as requiring a value
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Mark the property as not being changeable
+    #[allow(dead_code)]
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Specify the initial value of the property
+    pub fn with_value<V>(mut self, value: V) -> Self
+    where
+        V: Serialize,
+    {
+        self.value = Some(serde_json::to_value(value).unwrap());
+        self
+    }
+
+    /// Specify a regular expression that the value must match
+    #[allow(dead_code)]
+    pub fn with_regex<S>(mut self, regex: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.regex = Some(regex.into());
+        self
+    }
+
+    /// Specify the minimum numeric value of the property
+    #[allow(dead_code)]
+    pub fn with_min<N>(mut self, min: N) -> Self
+    where
+        N: Into<Number>,
+    {
+        self.min = Some(min.into());
+        self
+    }
+
+    /// Specify the maximum numeric value of the property
+    #[allow(dead_code)]
+    pub fn with_max<N>(mut self, max: N) -> Self
+    where
+        N: Into<Number>,
+    {
+        self.max = Some(max.into());
+        self
+    }
+
+    /// Specify the minimum length of the value of the property
+    #[allow(dead_code)]
+    pub fn with_min_length(mut self, min_length: u64) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// Specify the maximum length of the value of the property
+    #[allow(dead_code)]
+    pub fn with_max_length(mut self, max_length: u64) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Specify the set of values that the property can take
+    #[allow(dead_code)]
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = Some(options);
+        self
+    }
+}
+
+impl Options {
+    /// Create a set of options listing the values directly.
+    #[allow(dead_code)]
+    pub fn inline<I, V>(values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<OptionValue>,
+    {
+        Self {
+            inline: values.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Create a set of options where the values are listed by another resource.
+    #[allow(dead_code)]
+    pub fn link<L>(link: L) -> Self
+    where
+        L: Into<Link>,
+    {
+        Self {
+            link: Some(link.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Specify the minimum number of values that must be selected
+    #[allow(dead_code)]
+    pub fn with_min_items(mut self, min_items: u64) -> Self {
+        self.min_items = Some(min_items);
+        self
+    }
+
+    /// Specify the maximum number of values that can be selected
+    #[allow(dead_code)]
+    pub fn with_max_items(mut self, max_items: u64) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Specify a value that is initially selected
+    #[allow(dead_code)]
+    pub fn with_selected_value<S>(mut self, value: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.selected_values.push(value.into());
+        self
+    }
+}
+
+impl HalDocument {
+    /// Add a HAL-FORMS template to the resource.
+    /// By convention the main action on a resource is the template named `default`.
+    ///
+    /// # Parameters
+    /// - `name` - The name of the template
+    /// - `template` - The actual template
+    pub fn with_template<N>(mut self, name: N, template: Template) -> Self
+    where
+        N: Into<String>,
+    {
+        self.templates.insert(name.into(), template);
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+    use serde_json::json;
+
+    #[test]
+    fn minimal_template() {
+        let document =
+            HalDocument::new(json!({})).with_template("default", Template::new(&Method::DELETE));
+
+        check!(
+            serde_json::to_value(document).unwrap()
+                == json!({
+                    "_templates": {
+                        "default": { "method": "DELETE" }
+                    }
+                })
+        );
+    }
+
+    #[test]
+    fn full_template() {
+        let template = Template::new(&Method::PUT)
+            .with_title("Update document")
+            .with_target("/documents/1")
+            .with_content_type("application/json")
+            .with_property(
+                Property::new("id")
+                    .with_type(PropertyType::Hidden)
+                    .with_value(1)
+                    .read_only(),
+            )
+            .with_property(
+                Property::new("title")
+                    .with_type(PropertyType::Text)
+                    .with_prompt("Title")
+                    .with_regex("^[A-Z]")
+                    .with_min_length(1)
+                    .with_max_length(100)
+                    .required(),
+            )
+            .with_property(
+                Property::new("priority")
+                    .with_type(PropertyType::Number)
+                    .with_min(1)
+                    .with_max(5),
+            )
+            .with_property(
+                Property::new("status").with_options(
+                    Options::inline(vec!["draft", "published"])
+                        .with_max_items(1)
+                        .with_selected_value("draft"),
+                ),
+            )
+            .with_property(
+                Property::new("owner").with_options(Options::link("/users").with_min_items(1)),
+            );
+
+        check!(
+            serde_json::to_value(template).unwrap()
+                == json!({
+                    "title": "Update document",
+                    "method": "PUT",
+                    "target": "/documents/1",
+                    "contentType": "application/json",
+                    "properties": [
+                        { "name": "id", "type": "hidden", "readOnly": true, "value": 1 },
+                        {
+                            "name": "title",
+                            "type": "text",
+                            "prompt": "Title",
+                            "required": true,
+                            "regex": "^[A-Z]",
+                            "minLength": 1,
+                            "maxLength": 100
+                        },
+                        { "name": "priority", "type": "number", "min": 1, "max": 5 },
+                        {
+                            "name": "status",
+                            "options": {
+                                "inline": ["draft", "published"],
+                                "maxItems": 1,
+                                "selectedValues": ["draft"]
+                            }
+                        },
+                        {
+                            "name": "owner",
+                            "options": {
+                                "link": { "href": "/users" },
+                                "minItems": 1
+                            }
+                        }
+                    ]
+                })
+        );
+    }
+
+    #[test]
+    fn prompted_options() {
+        let options = Options::inline(vec![OptionValue::Prompted {
+            prompt: "Draft".to_owned(),
+            value: "draft".to_owned(),
+        }]);
+
+        check!(
+            serde_json::to_value(options).unwrap()
+                == json!({ "inline": [{ "prompt": "Draft", "value": "draft" }] })
+        );
+    }
+
+    #[test]
+    fn value_types() {
+        check!(
+            serde_json::to_value(PropertyType::DatetimeLocal).unwrap() == json!("datetime-local")
+        );
+        check!(serde_json::to_value(PropertyType::Textarea).unwrap() == json!("textarea"));
+    }
+}
@@ -0,0 +1,180 @@
+use super::{HalDocument, Links, CURIES};
+use crate::http::{
+    uri_template::{TemplateValues, UriTemplate},
+    SimpleRespondable,
+};
+use actix_http::http::{
+    header::{HeaderValue, LINK},
+    HeaderMap, Uri,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Append a `Link` header for some of the links from a HAL document.
+/// Relation types using a CURIE are expanded to the full URI, since the header has no equivalent.
+/// Extension relation types must be absolute URIs, so links whose CURIE expands to a relative
+/// URI are left out, as are templated links since the header can't represent them.
+/// Each link goes into a separate header value.
+///
+/// # Parameters
+/// - `headers` - The headers to append to
+/// - `links` - The links from the HAL document
+/// - `include` - Predicate to decide which relations, as named in the HAL document, to include
+pub fn append_link_header<F>(headers: &mut HeaderMap, links: &BTreeMap<String, Links>, include: F)
+where
+    F: Fn(&str) -> bool,
+{
+    let curies = links
+        .get(CURIES)
+        .map(|curies| {
+            curies
+                .iter()
+                .filter_map(|curie| {
+                    let name = curie.name.as_ref()?;
+                    let template = UriTemplate::parse(&curie.href).ok()?;
+                    Some((name.as_str(), template))
+                })
+                .collect::<BTreeMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    for (rel, links) in links {
+        if rel == CURIES || !include(rel) {
+            continue;
+        }
+
+        let expanded = rel.split_once(':').and_then(|(prefix, reference)| {
+            let template = curies.get(prefix)?;
+            Some(template.expand(&TemplateValues::default().with("rel", reference)))
+        });
+        let rel = match expanded {
+            Some(expanded) if is_absolute(&expanded) => expanded,
+            Some(expanded) => {
+                tracing::debug!(rel = ?rel, expanded = ?expanded, "Relation type is not an absolute URI");
+                continue;
+            }
+            None => rel.clone(),
+        };
+
+        for link in links.iter().filter_map(|link| link.to_header_value(&rel)) {
+            if let Ok(value) = HeaderValue::from_str(&link) {
+                headers.append(LINK, value);
+            } else {
+                tracing::warn!(link = ?link, "Failed to process link header");
+            }
+        }
+    }
+}
+
+/// Check whether a URI is absolute, and so can be used as an extension relation type.
+fn is_absolute(uri: &str) -> bool {
+    uri.parse::<Uri>()
+        .ok()
+        .and_then(|uri| uri.scheme().cloned())
+        .is_some()
+}
+
+impl SimpleRespondable<HalDocument> {
+    /// Mirror some of the links from the HAL document into the `Link` header, so that clients
+    /// that can't or won't read the body are still able to discover them.
+    ///
+    /// # Parameters
+    /// - `rels` - The relations, as named in the HAL document, to mirror into the header
+    pub fn with_link_header<I, S>(mut self, rels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let rels = rels.into_iter().map(Into::into).collect::<BTreeSet<_>>();
+
+        append_link_header(&mut self.headers, &self.body.links, |rel| {
+            rels.contains(rel)
+        });
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{hal::Curie, Respondable};
+    use assert2::check;
+    use serde_json::json;
+
+    fn link_headers(respondable: &SimpleRespondable<HalDocument>) -> Vec<String> {
+        let mut links = respondable
+            .headers()
+            .get_all(LINK)
+            .map(|value| value.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        links.sort();
+        links
+    }
+
+    #[test]
+    fn selected_links() {
+        let document = HalDocument::new(json!({}))
+            .with_link("self", "/users/1")
+            .with_link("item", "/users/1/items/1")
+            .with_link("item", "/users/1/items/2")
+            .with_link("other", "/other");
+
+        let respondable = SimpleRespondable::from(document).with_link_header(vec!["self", "item"]);
+
+        check!(
+            link_headers(&respondable)
+                == vec![
+                    r#"</users/1/items/1>; rel="item""#.to_owned(),
+                    r#"</users/1/items/2>; rel="item""#.to_owned(),
+                    r#"</users/1>; rel="self""#.to_owned(),
+                ]
+        );
+    }
+
+    #[test]
+    fn curie_links() {
+        let document = HalDocument::new(json!({}))
+            .with_curie(Curie::new("bb", "https://example.com/rels/{rel}"))
+            .with_link("bb:audit", "/audit");
+
+        let respondable =
+            SimpleRespondable::from(document).with_link_header(vec!["bb:audit", "curies"]);
+
+        check!(
+            link_headers(&respondable)
+                == vec![r#"</audit>; rel="https://example.com/rels/audit""#.to_owned()]
+        );
+    }
+
+    #[test]
+    fn relative_curie_links() {
+        let document = HalDocument::new(json!({}))
+            .with_curie(Curie::new("bb", "/rels/{rel}"))
+            .with_link("bb:audit", "/audit")
+            .with_link("self", "/users/1");
+
+        let respondable =
+            SimpleRespondable::from(document).with_link_header(vec!["bb:audit", "self"]);
+
+        check!(link_headers(&respondable) == vec![r#"</users/1>; rel="self""#.to_owned()]);
+    }
+
+    #[test]
+    fn templated_links() {
+        let document = HalDocument::new(json!({}))
+            .with_link("search", &UriTemplate::parse("/users{?name}").unwrap());
+
+        let respondable = SimpleRespondable::from(document).with_link_header(vec!["search"]);
+
+        check!(link_headers(&respondable).is_empty());
+    }
+
+    #[test]
+    fn body_unchanged() {
+        let document = HalDocument::new(json!({})).with_link("self", "/users/1");
+
+        let respondable = SimpleRespondable::from(document.clone()).with_link_header(vec!["self"]);
+
+        check!(respondable.body() == document);
+    }
+}
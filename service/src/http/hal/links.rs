@@ -1,29 +1,159 @@
-use serde::Serialize;
+use crate::http::uri_template::UriTemplate;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
 
 /// Representation of a single link in a resource.
-#[derive(Debug, Serialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct Link {
-    /// The actual link href.
+    /// The actual link href. If the link is templated then this is a URI Template.
     pub href: String,
+    /// Whether the href is a URI Template.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub templated: bool,
+    /// The optional media type expected when dereferencing the link.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    /// The optional URL providing information about the deprecation of the link.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<String>,
     /// The optional name of the link.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// The optional URI of a profile describing the target resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// The optional human-readable title of the link.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The optional language of the target resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hreflang: Option<String>,
+}
+
+impl Link {
+    /// Specify the name of the link
+    #[allow(dead_code)]
+    pub fn with_name<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Specify the media type expected when dereferencing the link
+    #[allow(dead_code)]
+    pub fn with_type<S>(mut self, media_type: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.media_type = Some(media_type.into());
+        self
+    }
+
+    /// Mark the link as deprecated, with a URL providing more information about the deprecation
+    #[allow(dead_code)]
+    pub fn with_deprecation<S>(mut self, deprecation: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.deprecation = Some(deprecation.into());
+        self
+    }
+
+    /// Specify the URI of a profile describing the target resource
+    #[allow(dead_code)]
+    pub fn with_profile<S>(mut self, profile: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Specify the human-readable title of the link
+    #[allow(dead_code)]
+    pub fn with_title<S>(mut self, title: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Specify the language of the target resource
+    #[allow(dead_code)]
+    pub fn with_hreflang<S>(mut self, hreflang: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.hreflang = Some(hreflang.into());
+        self
+    }
+}
+
+impl Link {
+    /// Format this link as a single value for an RFC 8288 `Link` header.
+    /// Templated links can't be represented in the header, so these give `None`.
+    ///
+    /// # Parameters
+    /// - `rel` - The relation type of the link
+    ///
+    /// # Returns
+    /// The header value, or `None` if the link can't be represented
+    pub fn to_header_value(&self, rel: &str) -> Option<String> {
+        if self.templated {
+            return None;
+        }
+
+        let mut value = format!("<{}>; rel=\"{}\"", self.href, quote(rel));
+        let params = [
+            ("type", &self.media_type),
+            ("title", &self.title),
+            ("hreflang", &self.hreflang),
+        ];
+        for (name, param) in &params {
+            if let Some(param) = param {
+                write!(value, "; {name}=\"{}\"", quote(param)).unwrap();
+            }
+        }
+
+        Some(value)
+    }
+}
+
+/// Escape a value so that it can be used inside a quoted string in a header.
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
-impl<S> From<S> for Link
-where
-    S: Into<String>,
-{
-    fn from(href: S) -> Self {
+impl From<String> for Link {
+    fn from(href: String) -> Self {
         Self {
-            href: href.into(),
-            name: None,
+            href,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&str> for Link {
+    fn from(href: &str) -> Self {
+        href.to_owned().into()
+    }
+}
+
+impl From<&UriTemplate> for Link {
+    fn from(template: &UriTemplate) -> Self {
+        Self {
+            href: template.to_string(),
+            templated: true,
+            ..Self::default()
         }
     }
 }
 
 /// Representation of a set of links for a single name.
-#[derive(Debug, Serialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Links {
     /// A single link.
@@ -48,6 +178,16 @@ impl Links {
 
         Links::Multiple(links)
     }
+
+    /// Iterate over all of the links in this representation.
+    pub fn iter(&self) -> impl Iterator<Item = &Link> {
+        let links: &[Link] = match self {
+            Links::Single(link) => std::slice::from_ref(link),
+            Links::Multiple(links) => links,
+        };
+
+        links.iter()
+    }
 }
 
 #[cfg(test)]
@@ -60,9 +200,109 @@ mod tests {
         let link = Link::from("/test");
 
         check!(link.href == "/test");
+        check!(link.templated == false);
         check!(link.name == None);
     }
 
+    #[test]
+    fn convert_template_to_link() {
+        let template = UriTemplate::parse("/users{?name,page}").unwrap();
+        let link = Link::from(&template);
+
+        check!(link.href == "/users{?name,page}");
+        check!(link.templated == true);
+    }
+
+    #[test]
+    fn serialize_minimal_link() {
+        let link = Link::from("/test");
+
+        check!(serde_json::to_value(link).unwrap() == serde_json::json!({"href": "/test"}));
+    }
+
+    #[test]
+    fn serialize_full_link() {
+        let template = UriTemplate::parse("/users{?name,page}").unwrap();
+        let link = Link::from(&template)
+            .with_type("application/hal+json")
+            .with_deprecation("https://example.com/deprecations/users")
+            .with_name("users")
+            .with_profile("https://example.com/profiles/user")
+            .with_title("Search users")
+            .with_hreflang("en-GB");
+
+        check!(
+            serde_json::to_value(link).unwrap()
+                == serde_json::json!({
+                    "href": "/users{?name,page}",
+                    "templated": true,
+                    "type": "application/hal+json",
+                    "deprecation": "https://example.com/deprecations/users",
+                    "name": "users",
+                    "profile": "https://example.com/profiles/user",
+                    "title": "Search users",
+                    "hreflang": "en-GB"
+                })
+        );
+    }
+
+    #[test]
+    fn deserialize_link() {
+        let link: Link = serde_json::from_value(serde_json::json!({
+            "href": "/users{?name}",
+            "templated": true,
+            "title": "Search users"
+        }))
+        .unwrap();
+
+        check!(
+            link == Link::from(&UriTemplate::parse("/users{?name}").unwrap())
+                .with_title("Search users")
+        );
+    }
+
+    #[test]
+    fn deserialize_links() {
+        let single: Links = serde_json::from_value(serde_json::json!({"href": "/abc"})).unwrap();
+        let multiple: Links =
+            serde_json::from_value(serde_json::json!([{"href": "/abc"}, {"href": "/def"}]))
+                .unwrap();
+
+        check!(single == Links::Single("/abc".into()));
+        check!(multiple == Links::Multiple(vec!["/abc".into(), "/def".into()]));
+    }
+
+    #[test]
+    fn minimal_header_value() {
+        check!(
+            Link::from("/test").to_header_value("self")
+                == Some(r#"</test>; rel="self""#.to_owned())
+        );
+    }
+
+    #[test]
+    fn full_header_value() {
+        let link = Link::from("/users/1")
+            .with_type("application/hal+json")
+            .with_title(r#"The "first" user"#)
+            .with_hreflang("en-GB");
+
+        check!(
+            link.to_header_value("item")
+                == Some(
+                    r#"</users/1>; rel="item"; type="application/hal+json"; title="The \"first\" user"; hreflang="en-GB""#
+                        .to_owned()
+                )
+        );
+    }
+
+    #[test]
+    fn templated_header_value() {
+        let template = UriTemplate::parse("/users{?name}").unwrap();
+
+        check!(Link::from(&template).to_header_value("search") == None);
+    }
+
     #[test]
     fn append_to_single_link() {
         let link_1 = Link::from("/abc");
@@ -0,0 +1,142 @@
+use super::{Embedded, HalDocument, Links};
+use crate::http::urls::UrlBuilder;
+
+impl Links {
+    /// Resolve the hrefs of all of these links into absolute URLs.
+    fn resolve(self, urls: &UrlBuilder) -> Self {
+        match self {
+            Links::Single(link) => Links::Single(urls.resolve_link(link)),
+            Links::Multiple(links) => Links::Multiple(
+                links
+                    .into_iter()
+                    .map(|link| urls.resolve_link(link))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl HalDocument {
+    /// Resolve every link in this resource into an absolute URL.
+    /// This includes the links in any embedded resources, and the targets of any HAL-FORMS
+    /// templates.
+    ///
+    /// # Parameters
+    /// - `urls` - The URL builder for the request being responded to
+    pub fn resolve_links(mut self, urls: &UrlBuilder) -> Self {
+        self.links = self
+            .links
+            .into_iter()
+            .map(|(name, links)| (name, links.resolve(urls)))
+            .collect();
+
+        self.embedded = self
+            .embedded
+            .into_iter()
+            .map(|(name, embedded)| {
+                let embedded = match embedded {
+                    Embedded::Single(document) => {
+                        Embedded::Single(Box::new(document.resolve_links(urls)))
+                    }
+                    Embedded::Multiple(documents) => Embedded::Multiple(
+                        documents
+                            .into_iter()
+                            .map(|document| document.resolve_links(urls))
+                            .collect(),
+                    ),
+                };
+                (name, embedded)
+            })
+            .collect();
+
+        for template in self.templates.values_mut() {
+            template.target = template
+                .target
+                .as_deref()
+                .map(|target| urls.resolve(target));
+
+            for options in template
+                .properties
+                .iter_mut()
+                .filter_map(|p| p.options.as_mut())
+            {
+                options.link = options.link.take().map(|link| urls.resolve_link(link));
+            }
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::hal::{Curie, Options, Property, Template};
+    use actix_http::http::Method;
+    use assert2::check;
+    use serde_json::json;
+
+    #[test]
+    fn resolve_all_links() {
+        let urls = UrlBuilder::new("https://example.com/api");
+
+        let document = HalDocument::new(json!({}))
+            .with_curie(Curie::new("bb", "/docs/rels/{rel}"))
+            .with_link("self", "/users/1")
+            .with_link("item", "/users/1/items/1")
+            .with_link("item", "https://other.com/items/2")
+            .with_embedded(
+                "friend",
+                HalDocument::new(json!({})).with_link("self", "/users/2"),
+            )
+            .with_template(
+                "default",
+                Template::new(&Method::PUT)
+                    .with_target("/users/1")
+                    .with_property(
+                        Property::new("country").with_options(Options::link("/countries")),
+                    ),
+            );
+
+        check!(
+            serde_json::to_value(document.resolve_links(&urls)).unwrap()
+                == json!({
+                    "_links": {
+                        "curies": [
+                            {
+                                "href": "https://example.com/api/docs/rels/{rel}",
+                                "name": "bb",
+                                "templated": true
+                            }
+                        ],
+                        "item": [
+                            { "href": "https://example.com/api/users/1/items/1" },
+                            { "href": "https://other.com/items/2" }
+                        ],
+                        "self": { "href": "https://example.com/api/users/1" }
+                    },
+                    "_embedded": {
+                        "friend": {
+                            "_links": {
+                                "self": { "href": "https://example.com/api/users/2" }
+                            }
+                        }
+                    },
+                    "_templates": {
+                        "default": {
+                            "method": "PUT",
+                            "target": "https://example.com/api/users/1",
+                            "properties": [
+                                {
+                                    "name": "country",
+                                    "options": {
+                                        "link": { "href": "https://example.com/api/countries" }
+                                    }
+                                }
+                            ]
+                        }
+                    }
+                })
+        );
+    }
+}
@@ -0,0 +1,100 @@
+pub use bigbang_derive::HalResource;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::hal::HalDocument;
+    use assert2::check;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, HalResource)]
+    struct Pet {
+        name: String,
+    }
+
+    #[derive(Serialize, HalResource)]
+    #[serde(rename_all = "camelCase")]
+    #[hal(self_link = "/people/{id}")]
+    #[hal(link(rel = "friends", href = "/people/{id}/friends{?since}"))]
+    #[hal(link(rel = "search", href = "/people{?name}", templated))]
+    struct Person {
+        id: u32,
+        display_name: String,
+        #[hal(embedded)]
+        best_friend: Option<Box<Person>>,
+        #[hal(embedded(rel = "pets"))]
+        #[serde(rename = "animals")]
+        owned_pets: Vec<Pet>,
+        #[hal(embedded)]
+        favourite_pet: Pet,
+    }
+
+    impl From<Box<Person>> for HalDocument {
+        fn from(person: Box<Person>) -> Self {
+            (*person).into()
+        }
+    }
+
+    #[test]
+    fn no_attributes() {
+        let document = HalDocument::from(Pet {
+            name: "Rex".to_owned(),
+        });
+
+        check!(document == HalDocument::new(json!({ "name": "Rex" })));
+    }
+
+    #[test]
+    fn all_attributes() {
+        let person = Person {
+            id: 1,
+            display_name: "Graham".to_owned(),
+            best_friend: Some(Box::new(Person {
+                id: 2,
+                display_name: "Fred".to_owned(),
+                best_friend: None,
+                owned_pets: vec![],
+                favourite_pet: Pet {
+                    name: "Felix".to_owned(),
+                },
+            })),
+            owned_pets: vec![Pet {
+                name: "Rex".to_owned(),
+            }],
+            favourite_pet: Pet {
+                name: "Rex".to_owned(),
+            },
+        };
+
+        check!(
+            serde_json::to_value(HalDocument::from(person)).unwrap()
+                == json!({
+                    "id": 1,
+                    "displayName": "Graham",
+                    "_links": {
+                        "friends": { "href": "/people/1/friends" },
+                        "search": { "href": "/people{?name}", "templated": true },
+                        "self": { "href": "/people/1" }
+                    },
+                    "_embedded": {
+                        "bestFriend": {
+                            "id": 2,
+                            "displayName": "Fred",
+                            "_links": {
+                                "friends": { "href": "/people/2/friends" },
+                                "search": { "href": "/people{?name}", "templated": true },
+                                "self": { "href": "/people/2" }
+                            },
+                            "_embedded": {
+                                "favouritePet": { "name": "Felix" },
+                                "pets": []
+                            }
+                        },
+                        "favouritePet": { "name": "Rex" },
+                        "pets": [{ "name": "Rex" }]
+                    }
+                })
+        );
+    }
+}
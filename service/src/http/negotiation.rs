@@ -0,0 +1,543 @@
+use super::{build_response, hal::append_link_header, problem::Problem, Respondable};
+use crate::server::Configurer;
+use actix_http::{
+    http::{
+        header::{q, Accept, Header, HeaderValue, Quality, CONTENT_TYPE, LINK, VARY},
+        HeaderMap, StatusCode,
+    },
+    Response as HttpResponse,
+};
+use actix_web::{
+    web::{Data, ServiceConfig},
+    HttpRequest,
+};
+use mime::Mime;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// The media type of HAL documents, which is the representation that all negotiable responses
+/// are built as.
+pub const HAL_JSON: &str = "application/hal+json";
+
+/// The media type of HAL-FORMS documents, which are HAL documents that also describe the actions
+/// available on the resource.
+pub const HAL_FORMS_JSON: &str = "application/prs.hal-forms+json";
+
+/// The result of rendering a HAL document into some other representation.
+#[derive(Debug)]
+pub struct Rendered {
+    /// Any additional headers to send with the representation.
+    pub headers: HeaderMap,
+    /// The body of the representation.
+    pub body: Value,
+}
+
+impl From<Value> for Rendered {
+    fn from(body: Value) -> Self {
+        Self {
+            headers: HeaderMap::new(),
+            body,
+        }
+    }
+}
+
+/// Trait implemented by anything that can render a HAL document into a representation that
+/// clients are able to ask for.
+pub trait Renderer: Send + Sync {
+    /// The media type of the representation that this renderer produces.
+    fn media_type(&self) -> Mime;
+
+    /// Render a HAL document into this representation.
+    ///
+    /// # Parameters
+    /// - `document` - The HAL document to render, in its serialized form
+    ///
+    /// # Returns
+    /// The rendered representation
+    fn render(&self, document: Value) -> Rendered;
+}
+
+/// Renderer that returns the HAL document without any HAL-FORMS templates.
+pub struct HalRenderer;
+
+impl Renderer for HalRenderer {
+    fn media_type(&self) -> Mime {
+        HAL_JSON.parse().unwrap()
+    }
+
+    fn render(&self, document: Value) -> Rendered {
+        match document {
+            Value::Object(mut document) => {
+                document.remove("_templates");
+                Value::Object(document).into()
+            }
+            document => document.into(),
+        }
+    }
+}
+
+/// Renderer that returns the HAL document unchanged, including any HAL-FORMS templates.
+pub struct HalFormsRenderer;
+
+impl Renderer for HalFormsRenderer {
+    fn media_type(&self) -> Mime {
+        HAL_FORMS_JSON.parse().unwrap()
+    }
+
+    fn render(&self, document: Value) -> Rendered {
+        document.into()
+    }
+}
+
+/// Renderer that returns the HAL document as plain JSON.
+/// Embedded resources become normal fields, and any links that don't need templating are moved
+/// into the `Link` header instead.
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn media_type(&self) -> Mime {
+        mime::APPLICATION_JSON
+    }
+
+    fn render(&self, document: Value) -> Rendered {
+        let mut headers = HeaderMap::new();
+
+        let document = match document {
+            Value::Object(mut document) => {
+                let links = document
+                    .remove("_links")
+                    .and_then(|links| serde_json::from_value(links).ok())
+                    .unwrap_or_default();
+
+                append_link_header(&mut headers, &links, |_| true);
+
+                Value::Object(strip_hal(document))
+            }
+            document => document,
+        };
+
+        Rendered {
+            headers,
+            body: document,
+        }
+    }
+}
+
+/// Strip the HAL specific members from a document, turning any embedded resources into normal
+/// fields. Links on embedded resources are discarded.
+fn strip_hal(mut document: Map<String, Value>) -> Map<String, Value> {
+    document.remove("_links");
+    document.remove("_templates");
+
+    if let Some(Value::Object(embedded)) = document.remove("_embedded") {
+        for (name, value) in embedded {
+            let value = match value {
+                Value::Object(resource) => Value::Object(strip_hal(resource)),
+                Value::Array(resources) => Value::Array(
+                    resources
+                        .into_iter()
+                        .map(|resource| match resource {
+                            Value::Object(resource) => Value::Object(strip_hal(resource)),
+                            resource => resource,
+                        })
+                        .collect(),
+                ),
+                value => value,
+            };
+
+            // Never clobber the resource's own data with an embedded resource of the same name.
+            document.entry(name).or_insert(value);
+        }
+    }
+
+    document
+}
+
+/// The set of renderers that HAL documents can be negotiated between.
+/// Renderers registered earlier are preferred when the client has no preference between them.
+#[derive(Clone)]
+pub struct Renderers {
+    renderers: Vec<Arc<dyn Renderer>>,
+}
+
+impl Default for Renderers {
+    fn default() -> Self {
+        Self {
+            renderers: vec![
+                Arc::new(HalRenderer),
+                Arc::new(JsonRenderer),
+                Arc::new(HalFormsRenderer),
+            ],
+        }
+    }
+}
+
+impl Renderers {
+    /// Register an additional renderer.
+    #[allow(dead_code)]
+    pub fn with_renderer(mut self, renderer: Arc<dyn Renderer>) -> Self {
+        self.renderers.push(renderer);
+
+        self
+    }
+
+    /// Select the renderer to use for a request.
+    ///
+    /// # Parameters
+    /// - `accept` - The `Accept` header from the request, if there was one
+    ///
+    /// # Returns
+    /// The most preferred renderer, or `None` if none of them are acceptable
+    pub fn select(&self, accept: Option<&Accept>) -> Option<&Arc<dyn Renderer>> {
+        let accept = match accept {
+            Some(accept) if !accept.is_empty() => accept,
+            _ => return self.renderers.first(),
+        };
+
+        let mut selected: Option<(Quality, &Arc<dyn Renderer>)> = None;
+        for renderer in &self.renderers {
+            let quality = quality(accept, &renderer.media_type());
+
+            if quality > q(0) && selected.is_none_or(|(best, _)| quality > best) {
+                selected = Some((quality, renderer));
+            }
+        }
+
+        selected.map(|(_, renderer)| renderer)
+    }
+
+    /// The media types that can be produced by the registered renderers.
+    pub fn media_types(&self) -> Vec<String> {
+        self.renderers
+            .iter()
+            .map(|renderer| renderer.media_type().essence_str().to_owned())
+            .collect()
+    }
+}
+
+impl Configurer for Renderers {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config.data(self.clone());
+    }
+}
+
+/// Determine how acceptable a media type is.
+/// This is the quality of the most specific media range that matches it, or zero if none do.
+fn quality(accept: &Accept, media_type: &Mime) -> Quality {
+    accept
+        .iter()
+        .filter_map(|range| {
+            let item = &range.item;
+            let specificity = if item.type_() == mime::STAR {
+                0
+            } else if item.type_() != media_type.type_() {
+                return None;
+            } else if item.subtype() == mime::STAR {
+                1
+            } else if item.subtype() == media_type.subtype() && item.suffix() == media_type.suffix()
+            {
+                2
+            } else {
+                return None;
+            };
+
+            Some((specificity, range.quality))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(q(0), |(_, quality)| quality)
+}
+
+/// Determine if a response is a HAL document, and so is able to be negotiated.
+fn is_hal(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Mime>().ok())
+        .is_some_and(|value| value.essence_str() == HAL_JSON)
+}
+
+/// Build the actual HTTP response for anything that can be responded with, negotiating the
+/// representation to use if the response is a HAL document.
+///
+/// # Parameters
+/// - `req` - The request that is being responded to
+/// - `respondable` - The response to send
+///
+/// # Returns
+/// The HTTP response, which will be a 406 Not Acceptable problem if the client can't accept
+/// any of the representations available
+pub fn build_negotiated_response<R>(req: &HttpRequest, respondable: R) -> HttpResponse
+where
+    R: Respondable,
+    R::Body: Serialize,
+{
+    let mut headers = respondable.headers();
+    if !is_hal(&headers) {
+        return build_response(respondable);
+    }
+
+    let default_renderers = Renderers::default();
+    let renderers = req
+        .app_data::<Data<Renderers>>()
+        .map_or(&default_renderers, |renderers| renderers.get_ref());
+
+    let accept = Accept::parse(req).ok();
+
+    let mut response = match renderers.select(accept.as_ref()) {
+        None => {
+            let problem = Problem::new(StatusCode::NOT_ACCEPTABLE)
+                .with_extension("acceptable", renderers.media_types());
+            build_response(problem)
+        }
+        Some(renderer) => {
+            let status_code = respondable.status_code();
+            let document = serde_json::to_value(respondable.body()).unwrap();
+            let Rendered {
+                headers: extra_headers,
+                body,
+            } = renderer.render(document);
+
+            headers.remove(CONTENT_TYPE);
+            if extra_headers.contains_key(LINK) {
+                // The renderer has already described all of the links, including any that were
+                // mirrored into the header already.
+                headers.remove(LINK);
+            }
+            for (key, value) in &extra_headers {
+                headers.append(key.clone(), value.clone());
+            }
+
+            let mut response = HttpResponse::build(status_code);
+            for (key, value) in &headers {
+                response.header(key.clone(), value.clone());
+            }
+            response.content_type(renderer.media_type().to_string());
+
+            response.json(body)
+        }
+    };
+
+    response
+        .headers_mut()
+        .append(VARY, HeaderValue::from_static("Accept"));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{
+        hal::{Curie, HalDocument, Template},
+        uri_template::UriTemplate,
+        SimpleRespondable,
+    };
+    use actix_http::body::{Body, ResponseBody};
+    use actix_http::http::Method;
+    use actix_web::test::TestRequest;
+    use assert2::check;
+    use serde_json::json;
+
+    struct SirenRenderer;
+
+    impl Renderer for SirenRenderer {
+        fn media_type(&self) -> Mime {
+            "application/vnd.siren+json".parse().unwrap()
+        }
+
+        fn render(&self, document: Value) -> Rendered {
+            json!({ "properties": document }).into()
+        }
+    }
+
+    fn select(renderers: &Renderers, accept: &str) -> Option<String> {
+        let req = TestRequest::default()
+            .header("accept", accept)
+            .to_http_request();
+        let accept = Accept::parse(&req).ok();
+
+        renderers
+            .select(accept.as_ref())
+            .map(|renderer| renderer.media_type().to_string())
+    }
+
+    fn document() -> HalDocument {
+        HalDocument::new(json!({ "name": "Test" }))
+            .with_curie(Curie::new("bb", "https://example.com/docs/rels/{rel}"))
+            .with_link("self", "/test")
+            .with_link("bb:audit", "/audit")
+            .with_link("search", &UriTemplate::parse("/search{?q}").unwrap())
+            .with_template("default", Template::new(&Method::PUT))
+            .with_embedded(
+                "child",
+                HalDocument::new(json!({ "id": 1 })).with_link("self", "/children/1"),
+            )
+    }
+
+    fn respond(req: &HttpRequest) -> (StatusCode, HeaderMap, Value) {
+        let response = build_negotiated_response(req, SimpleRespondable::from(document()));
+
+        let body = match response.body() {
+            ResponseBody::Body(Body::Bytes(bytes)) => serde_json::from_slice(bytes).unwrap(),
+            _ => panic!("Unexpected response body"),
+        };
+
+        (response.status(), response.headers().clone(), body)
+    }
+
+    #[test]
+    fn select_without_preference() {
+        let renderers = Renderers::default();
+
+        check!(renderers.select(None).unwrap().media_type().to_string() == HAL_JSON);
+        check!(select(&renderers, "*/*") == Some(HAL_JSON.to_owned()));
+        check!(select(&renderers, "application/*") == Some(HAL_JSON.to_owned()));
+    }
+
+    #[test]
+    fn select_exact() {
+        let renderers = Renderers::default();
+
+        check!(select(&renderers, "application/json") == Some("application/json".to_owned()));
+        check!(select(&renderers, "application/hal+json") == Some(HAL_JSON.to_owned()));
+        check!(
+            select(&renderers, "application/prs.hal-forms+json") == Some(HAL_FORMS_JSON.to_owned())
+        );
+    }
+
+    #[test]
+    fn select_by_quality() {
+        let renderers = Renderers::default();
+
+        check!(
+            select(&renderers, "application/hal+json;q=0.5, application/json")
+                == Some("application/json".to_owned())
+        );
+        check!(
+            select(&renderers, "application/*;q=0.5, application/json;q=0")
+                == Some(HAL_JSON.to_owned())
+        );
+    }
+
+    #[test]
+    fn select_unacceptable() {
+        let renderers = Renderers::default();
+
+        check!(select(&renderers, "text/html") == None);
+        check!(select(&renderers, "*/*;q=0") == None);
+    }
+
+    #[test]
+    fn select_additional_renderer() {
+        let renderers = Renderers::default().with_renderer(Arc::new(SirenRenderer));
+
+        check!(
+            select(
+                &renderers,
+                "application/vnd.siren+json, application/json;q=0.9"
+            ) == Some("application/vnd.siren+json".to_owned())
+        );
+    }
+
+    #[test]
+    fn respond_with_hal() {
+        let req = TestRequest::default().to_http_request();
+        let (status, headers, body) = respond(&req);
+
+        check!(status == StatusCode::OK);
+        check!(headers.get(CONTENT_TYPE).unwrap() == HAL_JSON);
+        check!(headers.get(VARY).unwrap() == "Accept");
+        check!(headers.get(LINK) == None);
+        check!(body.get("_templates") == None);
+        check!(body["_links"]["self"]["href"] == "/test");
+    }
+
+    #[test]
+    fn respond_with_hal_forms() {
+        let req = TestRequest::default()
+            .header("accept", "application/prs.hal-forms+json")
+            .to_http_request();
+        let (status, headers, body) = respond(&req);
+
+        check!(status == StatusCode::OK);
+        check!(headers.get(CONTENT_TYPE).unwrap() == HAL_FORMS_JSON);
+        check!(body == serde_json::to_value(document()).unwrap());
+    }
+
+    #[test]
+    fn respond_with_json() {
+        let req = TestRequest::default()
+            .header("accept", "application/json")
+            .to_http_request();
+        let (status, headers, body) = respond(&req);
+
+        let mut links = headers
+            .get_all(LINK)
+            .map(|value| value.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        links.sort();
+
+        check!(status == StatusCode::OK);
+        check!(headers.get(CONTENT_TYPE).unwrap() == "application/json");
+        check!(
+            links
+                == vec![
+                    r#"</audit>; rel="https://example.com/docs/rels/audit""#.to_owned(),
+                    r#"</test>; rel="self""#.to_owned(),
+                ]
+        );
+        check!(
+            body == json!({
+                "name": "Test",
+                "child": { "id": 1 }
+            })
+        );
+    }
+
+    #[test]
+    fn respond_with_registered_renderer() {
+        let renderers = Renderers::default().with_renderer(Arc::new(SirenRenderer));
+        let req = TestRequest::default()
+            .header("accept", "application/vnd.siren+json")
+            .data(renderers)
+            .to_http_request();
+        let (status, headers, body) = respond(&req);
+
+        check!(status == StatusCode::OK);
+        check!(headers.get(CONTENT_TYPE).unwrap() == "application/vnd.siren+json");
+        check!(body["properties"]["name"] == "Test");
+    }
+
+    #[test]
+    fn respond_not_acceptable() {
+        let req = TestRequest::default()
+            .header("accept", "text/html")
+            .to_http_request();
+        let (status, headers, body) = respond(&req);
+
+        check!(status == StatusCode::NOT_ACCEPTABLE);
+        check!(headers.get(CONTENT_TYPE).unwrap() == "application/problem+json");
+        check!(headers.get(VARY).unwrap() == "Accept");
+        check!(
+            body == json!({
+                "type": "about:blank",
+                "title": "Not Acceptable",
+                "status": 406,
+                "acceptable": ["application/hal+json", "application/json", "application/prs.hal-forms+json"]
+            })
+        );
+    }
+
+    #[test]
+    fn respond_with_other_content() {
+        let req = TestRequest::default()
+            .header("accept", "text/html")
+            .to_http_request();
+        let response = build_negotiated_response(&req, json!({ "name": "Test" }));
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers().get(CONTENT_TYPE).unwrap() == "application/json");
+        check!(response.headers().get(VARY) == None);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::server::Configurer;
+use actix_http::http::header::HeaderName;
+use actix_web::{
+    web::{Data, ServiceConfig},
+    HttpRequest,
+};
+
+/// The request header that the authenticating proxy in front of the service puts the principal
+/// making each request into.
+/// The service does no authentication of its own, so without this every request is anonymous.
+/// It must only be configured when the proxy always sets or strips the header, since otherwise
+/// anybody could claim to be anybody.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrincipalHeader(pub HeaderName);
+
+impl Configurer for PrincipalHeader {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config.data(self.clone());
+    }
+}
+
+/// The authenticated principal that a request is being made by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal(pub String);
+
+impl Principal {
+    /// Work out the authenticated principal that a request is being made by.
+    ///
+    /// # Parameters
+    /// - `req` - The request
+    ///
+    /// # Returns
+    /// The principal, or `None` if the request is anonymous
+    pub fn for_request(req: &HttpRequest) -> Option<Self> {
+        let header = req.app_data::<Data<PrincipalHeader>>()?;
+
+        req.headers()
+            .get(&header.0)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|principal| !principal.is_empty())
+            .map(|principal| Self(principal.to_owned()))
+    }
+}
+
+/// The request headers that the credentials for a request are taken from - for example
+/// `Authorization` or `Cookie`.
+/// Responses that depend on the principal declare that they vary by these.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CredentialHeaders(pub Vec<HeaderName>);
+
+impl CredentialHeaders {
+    /// Work out the request headers that the principal of a request is taken from.
+    ///
+    /// # Parameters
+    /// - `req` - The request
+    pub fn for_request(req: &HttpRequest) -> Self {
+        match req.app_data::<Data<PrincipalHeader>>() {
+            Some(header) => Self(vec![header.0.clone()]),
+            None => Self::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use assert2::check;
+
+    fn trusted() -> TestRequest {
+        TestRequest::default().data(PrincipalHeader(HeaderName::from_static("x-principal")))
+    }
+
+    #[test]
+    fn authenticated() {
+        let req = trusted()
+            .header("x-principal", " graham ")
+            .to_http_request();
+
+        check!(Principal::for_request(&req) == Some(Principal("graham".to_owned())));
+        check!(
+            CredentialHeaders::for_request(&req)
+                == CredentialHeaders(vec![HeaderName::from_static("x-principal")])
+        );
+    }
+
+    #[test]
+    fn anonymous() {
+        let missing = trusted().to_http_request();
+        let empty = trusted().header("x-principal", "").to_http_request();
+
+        check!(Principal::for_request(&missing) == None);
+        check!(Principal::for_request(&empty) == None);
+        check!(CredentialHeaders::for_request(&missing).0.len() == 1);
+    }
+
+    #[test]
+    fn untrusted() {
+        let req = TestRequest::default()
+            .header("x-principal", "graham")
+            .to_http_request();
+
+        check!(Principal::for_request(&req) == None);
+        check!(CredentialHeaders::for_request(&req) == CredentialHeaders::default());
+    }
+}
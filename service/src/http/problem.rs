@@ -0,0 +1,285 @@
+use super::{build_response, Respondable};
+use crate::server::Configurer;
+use actix_http::{
+    error::ResponseError,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    Response as HttpResponse,
+};
+use actix_web::{
+    error::{JsonPayloadError, PathError, QueryPayloadError},
+    web::{JsonConfig, PathConfig, QueryConfig, ServiceConfig},
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// The problem type for problems that are fully described by their status code.
+pub const BLANK_TYPE: &str = "about:blank";
+
+/// The base of the problem types for problems specific to this service.
+const TYPE_BASE: &str = "tag:grahamcox.co.uk,2021:bigbang/problems/";
+
+/// Representation of an RFC 7807 Problem Details response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// The URI identifying the type of problem.
+    pub type_uri: String,
+    /// A short, human-readable summary of the type of problem.
+    pub title: String,
+    /// The HTTP status code for the problem.
+    pub status: StatusCode,
+    /// A human-readable explanation specific to this occurrence of the problem.
+    pub detail: Option<String>,
+    /// A URI identifying this specific occurrence of the problem.
+    pub instance: Option<String>,
+    /// Any additional members describing the problem.
+    pub extensions: Map<String, Value>,
+}
+
+/// The body of a Problem Details response.
+#[derive(Debug, Serialize)]
+pub struct ProblemBody {
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Create a new problem that is fully described by the status code.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            type_uri: BLANK_TYPE.to_owned(),
+            title: status.canonical_reason().unwrap_or_default().to_owned(),
+            status,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Create a new problem of a type that is specific to this service.
+    ///
+    /// # Parameters
+    /// - `name` - The name of the problem type
+    /// - `title` - The summary of the problem type
+    /// - `status` - The HTTP status code for the problem
+    pub fn typed<T>(name: &str, title: T, status: StatusCode) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            type_uri: format!("{TYPE_BASE}{name}"),
+            title: title.into(),
+            ..Self::new(status)
+        }
+    }
+
+    /// Specify the explanation of this occurrence of the problem
+    pub fn with_detail<S>(mut self, detail: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Specify the URI identifying this occurrence of the problem
+    #[allow(dead_code)]
+    pub fn with_instance<S>(mut self, instance: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Specify an additional member describing the problem
+    pub fn with_extension<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Serialize,
+    {
+        let value = serde_json::to_value(value).unwrap();
+        self.extensions.insert(key.into(), value);
+        self
+    }
+}
+
+impl Respondable for Problem {
+    type Body = ProblemBody;
+
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, "application/problem+json".parse().unwrap());
+        headers
+    }
+
+    fn body(self) -> Self::Body {
+        ProblemBody {
+            type_uri: self.type_uri,
+            title: self.title,
+            status: self.status.as_u16(),
+            detail: self.detail,
+            instance: self.instance,
+            extensions: self.extensions,
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {detail}", self.title),
+            None => write!(f, "{}", self.title),
+        }
+    }
+}
+
+impl ResponseError for Problem {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        build_response(self.clone())
+    }
+}
+
+impl From<tokio_postgres::Error> for Problem {
+    fn from(e: tokio_postgres::Error) -> Self {
+        // The details of database errors are not for clients to see, so only log them.
+        tracing::error!(e = ?e, "Database error");
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+impl From<QueryPayloadError> for Problem {
+    fn from(e: QueryPayloadError) -> Self {
+        Self::typed(
+            "invalid-query",
+            "Invalid query string",
+            StatusCode::BAD_REQUEST,
+        )
+        .with_detail(e.to_string())
+    }
+}
+
+impl From<PathError> for Problem {
+    fn from(e: PathError) -> Self {
+        Self::new(StatusCode::NOT_FOUND).with_detail(e.to_string())
+    }
+}
+
+impl From<JsonPayloadError> for Problem {
+    fn from(e: JsonPayloadError) -> Self {
+        let status = match e {
+            JsonPayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            JsonPayloadError::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            _ => StatusCode::BAD_REQUEST,
+        };
+
+        Self::typed("invalid-body", "Invalid request body", status).with_detail(e.to_string())
+    }
+}
+
+/// Component that makes the standard extractors report their errors as problems.
+pub struct ExtractorProblems;
+
+impl Configurer for ExtractorProblems {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config
+            .app_data(JsonConfig::default().error_handler(|e, _| Problem::from(e).into()))
+            .app_data(QueryConfig::default().error_handler(|e, _| Problem::from(e).into()))
+            .app_data(PathConfig::default().error_handler(|e, _| Problem::from(e).into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+    use serde_json::json;
+
+    fn render(problem: &Problem) -> (StatusCode, String, Value) {
+        let response = problem.error_response();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        let body = match response.body() {
+            actix_http::body::ResponseBody::Body(actix_http::body::Body::Bytes(bytes)) => {
+                serde_json::from_slice(bytes).unwrap()
+            }
+            _ => panic!("Unexpected response body"),
+        };
+
+        (status, content_type, body)
+    }
+
+    #[test]
+    fn blank_problem() {
+        let (status, content_type, body) = render(&Problem::new(StatusCode::NOT_FOUND));
+
+        check!(status == StatusCode::NOT_FOUND);
+        check!(content_type == "application/problem+json");
+        check!(
+            body == json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404
+            })
+        );
+    }
+
+    #[test]
+    fn full_problem() {
+        let problem = Problem::typed(
+            "out-of-credit",
+            "You do not have enough credit",
+            StatusCode::FORBIDDEN,
+        )
+        .with_detail("Your current balance is 30, but that costs 50")
+        .with_instance("/account/12345/msgs/abc")
+        .with_extension("balance", 30)
+        .with_extension("accounts", vec!["/account/12345", "/account/67890"]);
+
+        let (status, _, body) = render(&problem);
+
+        check!(status == StatusCode::FORBIDDEN);
+        check!(
+            body == json!({
+                "type": "tag:grahamcox.co.uk,2021:bigbang/problems/out-of-credit",
+                "title": "You do not have enough credit",
+                "status": 403,
+                "detail": "Your current balance is 30, but that costs 50",
+                "instance": "/account/12345/msgs/abc",
+                "balance": 30,
+                "accounts": ["/account/12345", "/account/67890"]
+            })
+        );
+    }
+
+    #[test]
+    fn from_json_error() {
+        check!(Problem::from(JsonPayloadError::Overflow).status == StatusCode::PAYLOAD_TOO_LARGE);
+        check!(
+            Problem::from(JsonPayloadError::ContentType).status
+                == StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+}
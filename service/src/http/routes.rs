@@ -0,0 +1,257 @@
+use super::{
+    problem::Problem,
+    uri_template::{TemplateValues, UriTemplate},
+};
+use crate::server::Configurer;
+use actix_http::http::StatusCode;
+use actix_web::{
+    web::{resource, ServiceConfig},
+    Resource,
+};
+use std::collections::BTreeMap;
+
+/// A route that the service handles.
+/// The same path is used both to register the route with Actix and to generate links to it, so
+/// that the two can't disagree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Route {
+    /// The name of the route.
+    pub name: &'static str,
+    /// The path of the route. This is both the Actix resource path and a URI Template, so any
+    /// dynamic segments must be plain `{name}` variables.
+    pub path: &'static str,
+    /// The names of the query parameters that links to the route can include.
+    pub query: &'static [&'static str],
+}
+
+impl Route {
+    /// Create a new route.
+    ///
+    /// # Parameters
+    /// - `name` - The name of the route
+    /// - `path` - The path of the route
+    pub const fn new(name: &'static str, path: &'static str) -> Self {
+        Self {
+            name,
+            path,
+            query: &[],
+        }
+    }
+
+    /// Specify the query parameters that links to the route can include.
+    /// These are only used when generating links, and are left out of the link if they have no
+    /// value.
+    ///
+    /// # Parameters
+    /// - `query` - The names of the query parameters
+    pub const fn with_query(self, query: &'static [&'static str]) -> Self {
+        Self {
+            name: self.name,
+            path: self.path,
+            query,
+        }
+    }
+
+    /// Create the Actix resource to handle this route.
+    pub fn resource(&self) -> Resource {
+        resource(self.path).name(self.name)
+    }
+
+    /// Parse the path and query parameters of the route as a URI Template.
+    fn template(&self) -> Result<UriTemplate, String> {
+        let template = if self.query.is_empty() {
+            self.path.to_owned()
+        } else {
+            format!("{}{{?{}}}", self.path, self.query.join(","))
+        };
+
+        UriTemplate::parse(template.as_str()).map_err(|e| {
+            format!(
+                "Route {} has an invalid path {}: {}",
+                self.name, template, e
+            )
+        })
+    }
+}
+
+/// Error from generating a link to a route that has not been registered.
+#[derive(Debug, PartialEq)]
+pub struct UnknownRoute(pub &'static str);
+
+impl std::fmt::Display for UnknownRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Route {} has not been registered", self.0)
+    }
+}
+
+impl std::error::Error for UnknownRoute {}
+
+impl From<UnknownRoute> for Problem {
+    fn from(e: UnknownRoute) -> Self {
+        tracing::error!(e = ?e, "Generated a link to an unknown route");
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+/// Registry of the routes that the service handles.
+/// Links to routes are only ever generated through this, so a link can never be generated to a
+/// route that the service doesn't handle.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Routes {
+    routes: BTreeMap<&'static str, (Route, UriTemplate)>,
+}
+
+impl Routes {
+    /// Register a route.
+    ///
+    /// # Parameters
+    /// - `route` - The route
+    ///
+    /// # Panics
+    /// If the path and query parameters aren't a valid URI Template, or a different route has
+    /// already been registered with the same name
+    pub fn register(&mut self, route: Route) {
+        let template = route.template().unwrap_or_else(|e| panic!("{}", e));
+
+        match self.routes.get(route.name) {
+            Some((existing, _)) if existing == &route => {}
+            Some((existing, _)) => panic!(
+                "Route {} is already registered for {}, so can't be used for {}",
+                route.name, existing.path, route.path
+            ),
+            None => {
+                self.routes.insert(route.name, (route, template));
+            }
+        }
+    }
+
+    /// Get the URI Template for links to a route.
+    ///
+    /// # Parameters
+    /// - `route` - The route
+    ///
+    /// # Returns
+    /// The URI Template, which is relative to the base URL of the service
+    ///
+    /// # Errors
+    /// If the route has not been registered
+    pub fn template(&self, route: &Route) -> Result<&UriTemplate, UnknownRoute> {
+        match self.routes.get(route.name) {
+            Some((registered, template)) if registered == route => Ok(template),
+            _ => Err(UnknownRoute(route.name)),
+        }
+    }
+
+    /// Generate the path to a route.
+    ///
+    /// # Parameters
+    /// - `route` - The route
+    /// - `values` - The values of the variables in the path and query of the route
+    ///
+    /// # Returns
+    /// The path to the route, which is relative to the base URL of the service
+    ///
+    /// # Errors
+    /// If the route has not been registered
+    pub fn url_for(&self, route: &Route, values: &TemplateValues) -> Result<String, UnknownRoute> {
+        self.template(route).map(|template| template.expand(values))
+    }
+}
+
+impl Configurer for Routes {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config.data(self.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::{check, let_assert};
+
+    const HOME: Route = Route::new("home", "/");
+    const AUDIT: Route =
+        Route::new("audit", "/audit/{entity_type}/{entity_id}").with_query(&["cursor"]);
+
+    fn routes() -> Routes {
+        let mut routes = Routes::default();
+        routes.register(HOME);
+        routes.register(AUDIT);
+        routes
+    }
+
+    #[test]
+    fn url_for() {
+        let routes = routes();
+
+        check!(routes.url_for(&HOME, &TemplateValues::default()) == Ok("/".to_owned()));
+        check!(
+            routes.url_for(
+                &AUDIT,
+                &TemplateValues::default()
+                    .with("entity_type", "document")
+                    .with("entity_id", "a b")
+            ) == Ok("/audit/document/a%20b".to_owned())
+        );
+        check!(
+            routes.url_for(
+                &AUDIT,
+                &TemplateValues::default()
+                    .with("entity_type", "document")
+                    .with("entity_id", "1")
+                    .with("cursor", "abc")
+            ) == Ok("/audit/document/1?cursor=abc".to_owned())
+        );
+    }
+
+    #[test]
+    fn template() {
+        let routes = routes();
+        let_assert!(Ok(template) = routes.template(&AUDIT));
+
+        check!(
+            template == &UriTemplate::parse("/audit/{entity_type}/{entity_id}{?cursor}").unwrap()
+        );
+    }
+
+    #[test]
+    fn unknown_route() {
+        let routes = routes();
+
+        check!(
+            routes.url_for(&Route::new("other", "/other"), &TemplateValues::default())
+                == Err(UnknownRoute("other"))
+        );
+        check!(
+            routes.url_for(&Route::new("home", "/home"), &TemplateValues::default())
+                == Err(UnknownRoute("home"))
+        );
+        check!(UnknownRoute("other").to_string() == "Route other has not been registered");
+    }
+
+    #[test]
+    fn register_same_route_twice() {
+        let mut routes = routes();
+        routes.register(HOME);
+
+        check!(routes == self::routes());
+    }
+
+    #[test]
+    #[should_panic(expected = "Route home is already registered")]
+    fn register_conflicting_route() {
+        routes().register(Route::new("home", "/home"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Route broken has an invalid path /broken/{id")]
+    fn register_invalid_route() {
+        routes().register(Route::new("broken", "/broken/{id"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Route broken has an invalid path /broken{?a b}")]
+    fn register_invalid_query() {
+        routes().register(Route::new("broken", "/broken").with_query(&["a b"]));
+    }
+}
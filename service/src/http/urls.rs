@@ -0,0 +1,478 @@
+use super::{
+    hal::Link,
+    routes::{Route, Routes, UnknownRoute},
+    uri_template::{TemplateValues, UriTemplate},
+};
+use crate::server::Configurer;
+use actix_http::{
+    http::{
+        header::{HeaderName, FORWARDED, HOST},
+        uri::Authority,
+        HeaderMap,
+    },
+    Payload,
+};
+use actix_web::{
+    web::{Data, ServiceConfig},
+    FromRequest, HttpRequest,
+};
+use futures::future::{ok, Ready};
+use std::sync::Arc;
+
+/// The public base URL that the service is reachable at, when this is configured rather than
+/// worked out from the request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicBaseUrl(pub String);
+
+impl Configurer for PublicBaseUrl {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config.data(self.clone());
+    }
+}
+
+/// Marker to indicate that the service is only reachable through a proxy, and so that the
+/// `Forwarded` and `X-Forwarded-*` headers on requests can be trusted.
+/// Without this, anybody could make the service generate links to any host they like.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrustForwardedHeaders;
+
+impl Configurer for TrustForwardedHeaders {
+    fn configure_server(&self, config: &mut ServiceConfig) {
+        config.data(self.clone());
+    }
+}
+
+/// The non-standard headers that proxies use to describe the original request.
+const X_FORWARDED_PROTO: &str = "x-forwarded-proto";
+const X_FORWARDED_HOST: &str = "x-forwarded-host";
+const X_FORWARDED_PREFIX: &str = "x-forwarded-prefix";
+
+/// Request-scoped builder for turning the paths to our resources into absolute URLs.
+/// The base URL is the configured `PublicBaseUrl` if there is one. Otherwise it is worked out from
+/// the `Forwarded` or `X-Forwarded-*` headers set by the proxy in front of us, if these are
+/// trusted, falling back to the request itself.
+/// Links to the routes of the service can also be generated through it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlBuilder {
+    /// The base URL, without a trailing slash.
+    base: String,
+    /// The request headers that the base URL was worked out from.
+    vary: Vec<HeaderName>,
+    /// The routes that links can be generated to.
+    routes: Arc<Routes>,
+}
+
+impl UrlBuilder {
+    /// Create a URL builder with a known base URL.
+    ///
+    /// # Parameters
+    /// - `base` - The base URL that paths are relative to
+    pub fn new<S>(base: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut base = base.into();
+        while base.ends_with('/') {
+            base.pop();
+        }
+
+        Self {
+            base,
+            vary: vec![],
+            routes: Arc::default(),
+        }
+    }
+
+    /// Specify the routes that links can be generated to.
+    pub fn with_routes(mut self, routes: Arc<Routes>) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Specify the request headers that the base URL was worked out from.
+    fn with_vary(mut self, vary: Vec<HeaderName>) -> Self {
+        self.vary = vary;
+        self
+    }
+
+    /// Create a URL builder for the given request.
+    ///
+    /// # Parameters
+    /// - `req` - The request to build URLs for
+    pub fn for_request(req: &HttpRequest) -> Self {
+        let routes = req
+            .app_data::<Data<Routes>>()
+            .map(|routes| routes.clone().into_inner())
+            .unwrap_or_default();
+
+        if let Some(public) = req.app_data::<Data<PublicBaseUrl>>() {
+            return Self::new(public.0.clone()).with_routes(routes);
+        }
+
+        let headers = req.headers();
+        let mut vary = vec![HOST];
+
+        let (proto, host, prefix) = if req.app_data::<Data<TrustForwardedHeaders>>().is_some() {
+            vary.extend(vec![
+                FORWARDED,
+                HeaderName::from_static(X_FORWARDED_PROTO),
+                HeaderName::from_static(X_FORWARDED_HOST),
+                HeaderName::from_static(X_FORWARDED_PREFIX),
+            ]);
+
+            let forwarded = forwarded(headers);
+            (
+                forwarded
+                    .proto
+                    .or_else(|| first_value(headers, X_FORWARDED_PROTO)),
+                forwarded
+                    .host
+                    .or_else(|| first_value(headers, X_FORWARDED_HOST)),
+                first_value(headers, X_FORWARDED_PREFIX),
+            )
+        } else {
+            (None, None, None)
+        };
+
+        let scheme = proto
+            .map(|proto| proto.to_ascii_lowercase())
+            .filter(|proto| proto == "http" || proto == "https")
+            .or_else(|| req.uri().scheme_str().map(ToOwned::to_owned))
+            .unwrap_or_else(|| {
+                if req.app_config().secure() {
+                    "https".to_owned()
+                } else {
+                    "http".to_owned()
+                }
+            });
+        let host = host
+            .filter(|host| valid_host(host))
+            .or_else(|| first_value(headers, HOST.as_str()).filter(|host| valid_host(host)))
+            .or_else(|| req.uri().authority().map(ToString::to_string))
+            .unwrap_or_else(|| req.app_config().host().to_owned());
+        let prefix = prefix
+            .filter(|prefix| valid_prefix(prefix))
+            .map(|prefix| format!("/{}", prefix.trim_matches('/')))
+            .unwrap_or_default();
+
+        Self::new(format!("{scheme}://{host}{prefix}"))
+            .with_vary(vary)
+            .with_routes(routes)
+    }
+
+    /// The base URL that paths are relative to, without a trailing slash.
+    #[allow(dead_code)]
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The request headers that the base URL was worked out from, and so that any response
+    /// containing URLs from this builder varies by.
+    pub fn vary(&self) -> &[HeaderName] {
+        &self.vary
+    }
+
+    /// Resolve an href into an absolute URL.
+    /// Only hrefs that are absolute paths are changed. Anything that is already a full URL, or
+    /// that is relative to the document itself, is left alone.
+    ///
+    /// # Parameters
+    /// - `href` - The href to resolve. This can also be a URI Template.
+    ///
+    /// # Returns
+    /// The resolved href
+    pub fn resolve(&self, href: &str) -> String {
+        if href.starts_with('/') && !href.starts_with("//") {
+            format!("{}{}", self.base, href)
+        } else {
+            href.to_owned()
+        }
+    }
+
+    /// Resolve the href of a link into an absolute URL.
+    ///
+    /// # Parameters
+    /// - `link` - The link to resolve
+    ///
+    /// # Returns
+    /// The link, with the href resolved
+    pub fn resolve_link(&self, link: Link) -> Link {
+        Link {
+            href: self.resolve(&link.href),
+            ..link
+        }
+    }
+
+    /// Generate the absolute URL of a route.
+    ///
+    /// # Parameters
+    /// - `route` - The route
+    /// - `values` - The values of the variables in the path and query of the route
+    ///
+    /// # Returns
+    /// The absolute URL of the route
+    ///
+    /// # Errors
+    /// If the route has not been registered
+    pub fn route(&self, route: &Route, values: &TemplateValues) -> Result<String, UnknownRoute> {
+        self.routes
+            .url_for(route, values)
+            .map(|path| self.resolve(&path))
+    }
+
+    /// Get the URI Template for links to a route, for links that are expanded later on.
+    ///
+    /// # Parameters
+    /// - `route` - The route
+    ///
+    /// # Returns
+    /// The URI Template, which is relative to the base URL and so still needs resolving
+    ///
+    /// # Errors
+    /// If the route has not been registered
+    pub fn route_template(&self, route: &Route) -> Result<UriTemplate, UnknownRoute> {
+        self.routes.template(route).cloned()
+    }
+}
+
+impl FromRequest for UrlBuilder {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(Self::for_request(req))
+    }
+}
+
+/// The parameters from the `Forwarded` header that describe the original request.
+#[derive(Debug, Default)]
+struct Forwarded {
+    proto: Option<String>,
+    host: Option<String>,
+}
+
+/// Parse the `Forwarded` header, as defined in RFC 7239.
+/// Only the first element is used, since this is the one added by the proxy closest to the client.
+fn forwarded(headers: &HeaderMap) -> Forwarded {
+    let mut result = Forwarded::default();
+
+    let element = first_value(headers, "forwarded");
+    for pair in element.iter().flat_map(|element| element.split(';')) {
+        if let Some((name, value)) = pair.split_once('=') {
+            let value = value.trim().trim_matches('"');
+            if value.is_empty() {
+                continue;
+            }
+
+            match name.trim().to_ascii_lowercase().as_str() {
+                "proto" => result.proto = Some(value.to_owned()),
+                "host" => result.host = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+    }
+
+    result
+}
+
+/// Check whether a host - with optional port - from a request header is safe to put into links.
+fn valid_host(host: &str) -> bool {
+    !host.contains('@') && host.parse::<Authority>().is_ok()
+}
+
+/// Check whether a path prefix from a request header is safe to put into links.
+fn valid_prefix(prefix: &str) -> bool {
+    prefix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-._~/%".contains(c))
+}
+
+/// Get the first of the comma-separated values of a header.
+fn first_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use assert2::{check, let_assert};
+
+    fn base(req: TestRequest) -> String {
+        UrlBuilder::for_request(&req.to_http_request())
+            .base()
+            .to_owned()
+    }
+
+    fn behind_proxy() -> TestRequest {
+        TestRequest::default().data(TrustForwardedHeaders)
+    }
+
+    #[test]
+    fn from_request() {
+        check!(base(TestRequest::default()) == "http://localhost:8080");
+        check!(base(TestRequest::default().header("host", "example.com")) == "http://example.com");
+    }
+
+    #[test]
+    fn forwarded_header() {
+        let req = behind_proxy().header(
+            "forwarded",
+            r#"for=192.0.2.60;proto=https;host="example.com:8443", for=198.51.100.17;proto=http"#,
+        );
+
+        check!(base(req) == "https://example.com:8443");
+    }
+
+    #[test]
+    fn x_forwarded_headers() {
+        let req = behind_proxy()
+            .header("x-forwarded-proto", "https")
+            .header("x-forwarded-host", "example.com, proxy.internal")
+            .header("x-forwarded-prefix", "/api/");
+
+        check!(base(req) == "https://example.com/api");
+    }
+
+    #[test]
+    fn forwarded_takes_priority() {
+        let req = behind_proxy()
+            .header("forwarded", "proto=https;host=example.com")
+            .header("x-forwarded-proto", "http")
+            .header("x-forwarded-host", "other.com");
+
+        check!(base(req) == "https://example.com");
+    }
+
+    #[test]
+    fn untrusted_forwarded_headers() {
+        let req = TestRequest::default()
+            .header("forwarded", "proto=https;host=evil.example")
+            .header("x-forwarded-proto", "https")
+            .header("x-forwarded-host", "evil.example")
+            .header("x-forwarded-prefix", "/evil");
+
+        check!(base(req) == "http://localhost:8080");
+    }
+
+    #[test]
+    fn invalid_forwarded_headers() {
+        let req = behind_proxy()
+            .header("host", "example.com")
+            .header("x-forwarded-proto", "javascript")
+            .header("x-forwarded-host", "user@evil.example")
+            .header("x-forwarded-prefix", "/\"><script>");
+
+        check!(base(req) == "http://example.com");
+    }
+
+    #[test]
+    fn invalid_host_header() {
+        let req = TestRequest::default().header("host", "example.com/\"><script>");
+
+        check!(base(req) == "http://localhost:8080");
+    }
+
+    #[test]
+    fn public_base_url() {
+        let req = behind_proxy()
+            .data(PublicBaseUrl("https://api.example.com/bigbang/".to_owned()))
+            .header("x-forwarded-host", "other.com");
+
+        check!(base(req) == "https://api.example.com/bigbang");
+    }
+
+    #[test]
+    fn vary() {
+        let vary = |req: TestRequest| UrlBuilder::for_request(&req.to_http_request()).vary;
+
+        check!(vary(TestRequest::default()) == vec![HOST]);
+        check!(
+            vary(behind_proxy())
+                == vec![
+                    HOST,
+                    FORWARDED,
+                    HeaderName::from_static("x-forwarded-proto"),
+                    HeaderName::from_static("x-forwarded-host"),
+                    HeaderName::from_static("x-forwarded-prefix"),
+                ]
+        );
+        check!(
+            vary(TestRequest::default().data(PublicBaseUrl("https://example.com".to_owned())))
+                .is_empty()
+        );
+    }
+
+    #[actix_rt::test]
+    async fn extractor() {
+        let (req, mut payload) = behind_proxy()
+            .header("x-forwarded-host", "example.com")
+            .to_http_parts();
+
+        let_assert!(Ok(urls) = UrlBuilder::from_request(&req, &mut payload).await);
+        check!(urls.base() == "http://example.com");
+    }
+
+    #[test]
+    fn route() {
+        let audit = Route::new("audit", "/audit/{entity_type}/{entity_id}");
+        let mut routes = Routes::default();
+        routes.register(audit);
+        let urls = UrlBuilder::new("https://example.com").with_routes(Arc::new(routes));
+
+        check!(
+            urls.route(
+                &audit,
+                &TemplateValues::default()
+                    .with("entity_type", "document")
+                    .with("entity_id", "1")
+            ) == Ok("https://example.com/audit/document/1".to_owned())
+        );
+        check!(
+            urls.route(&Route::new("other", "/other"), &TemplateValues::default())
+                == Err(UnknownRoute("other"))
+        );
+    }
+
+    #[actix_rt::test]
+    async fn routes_for_request() {
+        let mut routes = Routes::default();
+        routes.register(Route::new("home", "/"));
+
+        let (req, mut payload) = TestRequest::default().data(routes).to_http_parts();
+
+        let_assert!(Ok(urls) = UrlBuilder::from_request(&req, &mut payload).await);
+        check!(
+            urls.route(&Route::new("home", "/"), &TemplateValues::default())
+                == Ok("http://localhost:8080/".to_owned())
+        );
+    }
+
+    #[test]
+    fn resolve() {
+        let urls = UrlBuilder::new("https://example.com/api");
+
+        check!(urls.resolve("/") == "https://example.com/api/");
+        check!(urls.resolve("/users/{id}") == "https://example.com/api/users/{id}");
+        check!(urls.resolve("https://other.com/") == "https://other.com/");
+        check!(urls.resolve("//other.com/") == "//other.com/");
+        check!(urls.resolve("users") == "users");
+    }
+
+    #[test]
+    fn resolve_link() {
+        let urls = UrlBuilder::new("https://example.com");
+        let link = Link::from("/users").with_title("Users");
+
+        check!(
+            urls.resolve_link(link) == Link::from("https://example.com/users").with_title("Users")
+        );
+    }
+}
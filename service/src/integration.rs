@@ -1,35 +1,88 @@
 mod database;
+mod schema;
 mod tests;
 
+use std::{path::Path, time::Duration};
+
 use actix_http::Request;
 use database::TestDatabase;
+pub use schema::dump_schema;
 
 use crate::service::{testing::TestResponse, Service, Settings};
 
 /// Test Suite to make testing the service easier.
 pub struct TestSuite {
+    service: Service,
+    // Must be dropped after everything else, so that nothing is still using it.
     #[allow(dead_code)]
     database: TestDatabase,
-    service: Service,
 }
 
 impl TestSuite {
     /// Create a new test suite.
     pub async fn new() -> Self {
+        Self::with_fixtures(&[]).await
+    }
+
+    /// Create a new test suite, with some fixtures loaded into the database.
+    ///
+    /// # Parameters
+    /// - `fixtures` - The fixture files to load, relative to the `tests/fixtures` directory
+    pub async fn with_fixtures(fixtures: &[&str]) -> Self {
+        Self::with_settings(fixtures, |_| {}).await
+    }
+
+    /// Create a new test suite, with some fixtures loaded into the database and some of the
+    /// settings changed from the defaults.
+    ///
+    /// # Parameters
+    /// - `fixtures` - The fixture files to load, relative to the `tests/fixtures` directory
+    /// - `configure` - Callback to change the settings
+    pub async fn with_settings<F>(fixtures: &[&str], configure: F) -> Self
+    where
+        F: FnOnce(&mut Settings),
+    {
         let _ = env_logger::try_init();
 
-        let database = TestDatabase::new();
-        let service = Service::new(Settings {
+        let database = TestDatabase::new().await;
+        let mut settings = Settings {
             port: 0,
             database_url: database.url.clone(),
-        })
-        .await;
+            database_metrics_namespace: "bigbang".to_owned(),
+            legacy_database_metrics: false,
+            database_sql_comments: false,
+            database_migration_lock_timeout: Duration::from_mins(1),
+            public_base_url: None,
+            trust_forwarded_headers: false,
+            principal_header: None,
+            audit_principals: vec![],
+            home_link_timeout: Duration::from_secs(1),
+        };
+        configure(&mut settings);
+
+        let service = Service::new(settings)
+            .await
+            .expect("Failed to build service");
+
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let fixtures = fixtures
+            .iter()
+            .map(|fixture| fixtures_dir.join(fixture))
+            .collect::<Vec<_>>();
+        crate::database::fixtures::load(service.database(), &fixtures)
+            .await
+            .expect("Failed to load fixtures");
 
-        Self { database, service }
+        Self { service, database }
     }
 
     /// Inject a request into the service and get the response.
     pub async fn inject(&self, req: Request) -> TestResponse {
         self.service.inject(req).await
     }
+
+    /// Get direct access to the database that the service is using.
+    pub fn db(&self) -> &crate::database::Database {
+        self.service.database()
+    }
 }
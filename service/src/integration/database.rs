@@ -1,38 +1,235 @@
+mod local;
 mod postgres;
 
-use lazy_static::lazy_static;
+use std::process::{Command, Stdio};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    LazyLock,
+};
+
+use futures::Future;
+use local::LocalPostgres;
 use postgres::Postgres;
+use prometheus::Registry;
 use testcontainers::{clients::Cli, Container, Docker};
 
-lazy_static! {
-    static ref DOCKER: Cli = Cli::default();
+/// The name of the database that has all of the migrations applied, and that every test database is
+/// cloned from.
+const TEMPLATE_DATABASE: &str = "bigbang_template";
+
+static DOCKER: LazyLock<Cli> = LazyLock::new(Cli::default);
+static SERVER: LazyLock<DatabaseServer> = LazyLock::new(DatabaseServer::start);
+
+/// Counter used to give every test database a unique name.
+static NEXT_DATABASE: AtomicU32 = AtomicU32::new(0);
+
+/// The ways in which the Postgres server can be provided.
+enum Backend {
+    /// Postgres running in a Docker container.
+    Docker(Container<'static, Cli, Postgres>),
+    /// Postgres running from the locally installed binaries.
+    Local(LocalPostgres),
 }
 
-pub struct TestDatabase {
-    #[allow(dead_code)]
-    node: Container<'static, Cli, Postgres>,
-    pub host: String,
-    pub port: u16,
-    pub url: String,
+/// The Postgres server that is shared between every test in the test binary.
+struct DatabaseServer {
+    backend: Backend,
+    host: String,
+    port: u16,
 }
 
-impl TestDatabase {
-    /// Construct a new test database.
-    #[allow(clippy::new_without_default)] // This is a non-trivial implementation. Hiding it behind default() is unwise.
-    pub fn new() -> Self {
-        tracing::info!("Starting Postgres database");
+impl DatabaseServer {
+    /// Start the Postgres server and prepare the template database on it.
+    ///
+    /// The `TEST_DATABASE_BACKEND` environment variable can be set to either `docker` or `local` to
+    /// pick how the server is run. If not set then Docker is used if it's available, and the local
+    /// Postgres binaries otherwise.
+    fn start() -> Self {
+        let backend = std::env::var("TEST_DATABASE_BACKEND").ok();
+        let server = match backend.as_deref() {
+            Some("docker") => Self::start_docker(),
+            Some("local") => Self::start_local(),
+            Some(other) => panic!("Unknown test database backend: {}", other),
+            None if docker_available() => Self::start_docker(),
+            None if local::is_available() => Self::start_local(),
+            None => panic!("Neither Docker nor a local Postgres installation is available"),
+        };
+        tracing::info!(url = ?server.url("postgres"), "Running postgres");
+
+        // Statics are never dropped, so the server needs stopping explicitly when the test binary exits.
+        // Safety: `remove_server` is a plain function with no preconditions that never unwinds.
+        unsafe {
+            libc::atexit(remove_server);
+        }
+
+        server.create_template();
+
+        server
+    }
+
+    /// Start Postgres in a Docker container.
+    fn start_docker() -> Self {
+        tracing::info!("Starting Postgres database in Docker");
         let node = DOCKER.run(Postgres::default());
 
         let host = std::env::var("DOCKER_HOSTNAME").unwrap_or_else(|_| "localhost".to_owned());
         let port = node.get_host_port(5432).unwrap();
-        let url = format!("postgres://postgres@{}:{}", host, port);
-        tracing::info!(url = ?url, "Running postgres");
 
         Self {
-            node,
+            backend: Backend::Docker(node),
             host,
             port,
-            url,
         }
     }
+
+    /// Start Postgres from the local binaries.
+    fn start_local() -> Self {
+        tracing::info!("Starting Postgres database from local binaries");
+        let postgres = LocalPostgres::start();
+        let port = postgres.port;
+
+        Self {
+            backend: Backend::Local(postgres),
+            host: "127.0.0.1".to_owned(),
+            port,
+        }
+    }
+
+    /// Build the URL to connect to the named database on this server.
+    fn url(&self, database: &str) -> String {
+        format!("postgres://postgres@{}:{}/{database}", self.host, self.port)
+    }
+
+    /// Create the template database and apply all of the migrations to it.
+    fn create_template(&self) {
+        let admin_url = self.url("postgres");
+        let template_url = self.url(TEMPLATE_DATABASE);
+
+        block_on(move || async move {
+            tracing::info!("Creating template database");
+            execute(&admin_url, &format!("CREATE DATABASE {TEMPLATE_DATABASE}")).await;
+
+            crate::database::component::Builder::new(&template_url)
+                .build(&Registry::new())
+                .await
+                .expect("Failed to migrate template database");
+
+            // Nothing may be connected to the template whilst it's being cloned.
+            execute(
+                &admin_url,
+                &format!("ALTER DATABASE {TEMPLATE_DATABASE} ALLOW_CONNECTIONS false"),
+            )
+            .await;
+            terminate_connections(&admin_url, TEMPLATE_DATABASE).await;
+            tracing::info!("Created template database");
+        });
+    }
+}
+
+extern "C" fn remove_server() {
+    let _ = std::panic::catch_unwind(|| match &SERVER.backend {
+        Backend::Docker(node) => node.rm(),
+        Backend::Local(postgres) => postgres.stop(),
+    });
+}
+
+/// A database for the use of a single test, cloned from the template database and dropped again
+/// when the test is finished with it.
+pub struct TestDatabase {
+    name: String,
+    pub url: String,
+}
+
+impl TestDatabase {
+    /// Construct a new test database.
+    pub async fn new() -> Self {
+        let server = &*SERVER;
+
+        let name = format!(
+            "bigbang_test_{}",
+            NEXT_DATABASE.fetch_add(1, Ordering::SeqCst)
+        );
+        tracing::debug!(name = ?name, "Creating test database");
+        execute(
+            &server.url("postgres"),
+            &format!("CREATE DATABASE {name} TEMPLATE {TEMPLATE_DATABASE}"),
+        )
+        .await;
+
+        let url = server.url(&name);
+        tracing::info!(url = ?url, "Created test database");
+
+        Self { name, url }
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        // If the test failed then leave the database behind. It goes away with the server anyway.
+        if std::thread::panicking() {
+            return;
+        }
+
+        tracing::debug!(name = ?self.name, "Dropping test database");
+        let admin_url = SERVER.url("postgres");
+        let name = self.name.clone();
+
+        block_on(move || async move {
+            terminate_connections(&admin_url, &name).await;
+            execute(&admin_url, &format!("DROP DATABASE {name}")).await;
+        });
+    }
+}
+
+/// Check if a Docker daemon is available to run containers in.
+fn docker_available() -> bool {
+    Command::new("docker")
+        .arg("info")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Execute some SQL against the database at the given URL on a brand new connection.
+async fn execute(url: &str, sql: &str) {
+    let (client, connection) = tokio_postgres::connect(url, tokio_postgres::NoTls)
+        .await
+        .expect("Failed to connect to test database server");
+    actix_rt::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::warn!(e = ?e, "Test database connection failed");
+        }
+    });
+
+    client
+        .batch_execute(sql)
+        .await
+        .expect("Failed to execute SQL against test database server");
+}
+
+/// Forcibly close every connection to the named database.
+async fn terminate_connections(admin_url: &str, database: &str) {
+    execute(
+        admin_url,
+        &format!(
+            "SELECT pg_terminate_backend(pid) FROM pg_stat_activity WHERE datname = '{database}'"
+        ),
+    )
+    .await;
+}
+
+/// Run some asynchronous work to completion from synchronous code.
+///
+/// The work is run on a dedicated thread with its own runtime, since the calling thread is likely
+/// to already be inside the runtime of an `actix_rt::test`.
+fn block_on<F, R, O>(f: F) -> O
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Future<Output = O> + 'static,
+    O: Send + 'static,
+{
+    std::thread::spawn(move || actix_rt::System::new("test-database").block_on(f()))
+        .join()
+        .expect("Failed to run database work")
 }
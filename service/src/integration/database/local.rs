@@ -0,0 +1,137 @@
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// A throwaway Postgres cluster run from the locally installed Postgres binaries.
+pub struct LocalPostgres {
+    directory: TempDir,
+    bin_dir: Option<PathBuf>,
+    pub port: u16,
+}
+
+impl LocalPostgres {
+    /// Initialise a new cluster in a temporary directory and start it on a free port.
+    pub fn start() -> Self {
+        let directory = tempfile::Builder::new()
+            .prefix("bigbang-postgres")
+            .tempdir()
+            .expect("Failed to create directory for Postgres cluster");
+        let bin_dir = std::env::var_os("TEST_POSTGRES_BIN").map(PathBuf::from);
+        let port = free_port();
+
+        let postgres = Self {
+            directory,
+            bin_dir,
+            port,
+        };
+
+        postgres
+            .run(
+                postgres
+                    .command("initdb")
+                    .arg("--pgdata")
+                    .arg(postgres.data_dir())
+                    .args(["--username", "postgres", "--auth", "trust", "--no-sync"]),
+            )
+            .unwrap_or_else(|e| panic!("{}", e));
+        postgres
+            .run(
+                postgres
+                    .command("pg_ctl")
+                    .arg("--pgdata")
+                    .arg(postgres.data_dir())
+                    .arg("--log")
+                    .arg(postgres.directory.path().join("postgres.log"))
+                    .arg("--options")
+                    .arg(format!(
+                        "-p {port} -k {} -c listen_addresses=127.0.0.1 -F",
+                        postgres.directory.path().display()
+                    ))
+                    .args(["--wait", "start"]),
+            )
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        postgres
+    }
+
+    /// Stop the cluster and remove the directory it lived in.
+    /// This never panics, since it's run whilst unwinding if starting the cluster failed. Any failure
+    /// to stop the cluster is logged instead.
+    pub fn stop(&self) {
+        let result = self.run(
+            self.command("pg_ctl")
+                .arg("--pgdata")
+                .arg(self.data_dir())
+                .args(["--mode", "immediate", "--wait", "stop"]),
+        );
+        if let Err(e) = result {
+            tracing::warn!(e = ?e, "Failed to stop Postgres");
+        }
+
+        let _ = std::fs::remove_dir_all(self.directory.path());
+    }
+
+    fn data_dir(&self) -> PathBuf {
+        self.directory.path().join("data")
+    }
+
+    fn command(&self, name: &str) -> Command {
+        match &self.bin_dir {
+            Some(bin_dir) => Command::new(bin_dir.join(name)),
+            None => Command::new(name),
+        }
+    }
+
+    /// Run a Postgres command.
+    ///
+    /// # Errors
+    /// If the command couldn't be run or didn't succeed, with a description of what went wrong
+    fn run(&self, command: &mut Command) -> Result<(), String> {
+        tracing::debug!(command = ?command, "Running Postgres command");
+        let output = command
+            .output()
+            .map_err(|e| format!("Failed to run {command:?}: {e}"))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Postgres command {command:?} failed: {}\n{}",
+                String::from_utf8_lossy(&output.stderr),
+                log_contents(self.directory.path())
+            ))
+        }
+    }
+}
+
+impl Drop for LocalPostgres {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Check if the local Postgres binaries can be found.
+pub fn is_available() -> bool {
+    let initdb = match std::env::var_os("TEST_POSTGRES_BIN") {
+        Some(bin_dir) => PathBuf::from(bin_dir).join("initdb"),
+        None => PathBuf::from("initdb"),
+    };
+
+    Command::new(initdb)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Find a port on the loopback interface that nothing is currently listening on.
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|address| address.port())
+        .expect("Failed to find a free port")
+}
+
+fn log_contents(directory: &Path) -> String {
+    std::fs::read_to_string(directory.join("postgres.log")).unwrap_or_default()
+}
@@ -1,12 +1,10 @@
 use std::collections::HashMap;
-use testcontainers::core::Port;
 use testcontainers::{Container, Docker, Image, WaitForMessage};
 
 #[derive(Debug)]
 pub struct Postgres {
     arguments: PostgresArgs,
     env_vars: HashMap<String, String>,
-    ports: Option<Vec<Port>>,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -30,7 +28,6 @@ impl Default for Postgres {
         Self {
             arguments: PostgresArgs::default(),
             env_vars,
-            ports: None,
         }
     }
 }
@@ -0,0 +1,177 @@
+use crate::database::{Database, Transaction};
+
+/// Dump the schema of the database in a normalised text form, suitable for snapshot testing.
+///
+/// Everything outside of the system schemas is included - tables and views with their columns,
+/// constraints, indexes and triggers, followed by sequences, enum types and functions. Everything
+/// is sorted by name, except for columns which are in table order, so that the output is stable
+/// between runs.
+///
+/// # Parameters
+/// - `db` - The database to dump the schema of
+///
+/// # Returns
+/// The text form of the schema
+pub async fn dump_schema(db: &Database) -> String {
+    let mut conn = db.connect().await;
+    let tx = conn.begin().await;
+
+    let mut lines = vec![];
+
+    for (schema, table, table_type) in list_tables(&tx).await {
+        lines.push(format!("{table_type} {schema}.{table}"));
+        lines.append(&mut list_columns(&tx, &schema, &table).await);
+        lines.append(&mut list_constraints(&tx, &schema, &table).await);
+        lines.append(&mut list_indexes(&tx, &schema, &table).await);
+        lines.append(&mut list_triggers(&tx, &schema, &table).await);
+    }
+
+    lines.append(&mut list_sequences(&tx).await);
+    lines.append(&mut list_enums(&tx).await);
+    lines.append(&mut list_functions(&tx).await);
+
+    tx.commit().await.expect("Failed to commit transaction");
+
+    lines.join("\n")
+}
+
+/// Restriction to apply to a `pg_namespace` alias of `n` to only include user schemas.
+const USER_SCHEMAS: &str = "n.nspname NOT IN ('pg_catalog', 'information_schema', 'pg_toast')";
+
+async fn query_strings(
+    tx: &Transaction<'_>,
+    sql: &str,
+    params: &[&(dyn postgres_types::ToSql + Sync)],
+) -> Vec<String> {
+    tx.query(sql, params)
+        .await
+        .expect("Failed to query database schema")
+        .iter()
+        .map(|row| row.get::<_, String>(0))
+        .collect()
+}
+
+async fn list_tables(tx: &Transaction<'_>) -> Vec<(String, String, String)> {
+    tx.query(
+        "SELECT table_schema::TEXT, table_name::TEXT, table_type::TEXT
+        FROM information_schema.tables
+        WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
+        ORDER BY table_schema, table_name",
+        &[],
+    )
+    .await
+    .expect("Failed to list tables")
+    .iter()
+    .map(|row| {
+        let table_type = match row.get::<_, &str>(2) {
+            "BASE TABLE" => "TABLE".to_owned(),
+            other => other.to_owned(),
+        };
+        (row.get(0), row.get(1), table_type)
+    })
+    .collect()
+}
+
+async fn list_columns(tx: &Transaction<'_>, schema: &str, table: &str) -> Vec<String> {
+    query_strings(
+        tx,
+        "SELECT '  COLUMN ' || a.attname || ' ' || format_type(a.atttypid, a.atttypmod)
+            || CASE WHEN a.attnotnull THEN ' NOT NULL' ELSE '' END
+            || COALESCE(' DEFAULT ' || pg_get_expr(d.adbin, d.adrelid), '')
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+        WHERE n.nspname = $1 AND c.relname = $2 AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY a.attnum",
+        &[&schema, &table],
+    )
+    .await
+}
+
+async fn list_constraints(tx: &Transaction<'_>, schema: &str, table: &str) -> Vec<String> {
+    query_strings(
+        tx,
+        "SELECT '  CONSTRAINT ' || con.conname || ' ' || pg_get_constraintdef(con.oid)
+        FROM pg_constraint con
+        JOIN pg_class c ON c.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
+        ORDER BY con.conname",
+        &[&schema, &table],
+    )
+    .await
+}
+
+async fn list_indexes(tx: &Transaction<'_>, schema: &str, table: &str) -> Vec<String> {
+    query_strings(
+        tx,
+        "SELECT '  INDEX ' || indexdef
+        FROM pg_indexes
+        WHERE schemaname = $1 AND tablename = $2
+        ORDER BY indexname",
+        &[&schema, &table],
+    )
+    .await
+}
+
+async fn list_triggers(tx: &Transaction<'_>, schema: &str, table: &str) -> Vec<String> {
+    query_strings(
+        tx,
+        "SELECT '  TRIGGER ' || pg_get_triggerdef(t.oid)
+        FROM pg_trigger t
+        JOIN pg_class c ON c.oid = t.tgrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2 AND NOT t.tgisinternal
+        ORDER BY t.tgname",
+        &[&schema, &table],
+    )
+    .await
+}
+
+async fn list_sequences(tx: &Transaction<'_>) -> Vec<String> {
+    query_strings(
+        tx,
+        "SELECT 'SEQUENCE ' || sequence_schema || '.' || sequence_name || ' ' || data_type
+            || ' START ' || start_value || ' INCREMENT ' || increment
+        FROM information_schema.sequences
+        ORDER BY sequence_schema, sequence_name",
+        &[],
+    )
+    .await
+}
+
+async fn list_enums(tx: &Transaction<'_>) -> Vec<String> {
+    query_strings(
+        tx,
+        &format!(
+            "SELECT 'ENUM ' || n.nspname || '.' || t.typname || ' ('
+                || string_agg(quote_literal(e.enumlabel), ', ' ORDER BY e.enumsortorder) || ')'
+            FROM pg_type t
+            JOIN pg_namespace n ON n.oid = t.typnamespace
+            JOIN pg_enum e ON e.enumtypid = t.oid
+            WHERE {USER_SCHEMAS}
+            GROUP BY n.nspname, t.typname
+            ORDER BY n.nspname, t.typname"
+        ),
+        &[],
+    )
+    .await
+}
+
+async fn list_functions(tx: &Transaction<'_>) -> Vec<String> {
+    query_strings(
+        tx,
+        &format!(
+            "SELECT 'FUNCTION ' || n.nspname || '.' || p.proname
+                || '(' || pg_get_function_identity_arguments(p.oid) || ') RETURNS '
+                || pg_get_function_result(p.oid)
+            FROM pg_proc p
+            JOIN pg_namespace n ON n.oid = p.pronamespace
+            WHERE {USER_SCHEMAS}
+            ORDER BY n.nspname, p.proname, pg_get_function_identity_arguments(p.oid)"
+        ),
+        &[],
+    )
+    .await
+}
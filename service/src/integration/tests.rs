@@ -1 +1,9 @@
+mod audit;
+mod documents;
+mod fixtures;
 mod home;
+mod migrate;
+mod pagination;
+mod schema;
+mod sqlcommenter;
+mod versioned;
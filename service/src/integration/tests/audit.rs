@@ -0,0 +1,209 @@
+use crate::database::audit::{AuditContext, AuditedEntity};
+use crate::integration::TestSuite;
+use actix_http::{http::header::HeaderName, Request};
+use actix_web::test::TestRequest;
+use assert2::{check, let_assert};
+use serde_json::json;
+use uuid::Uuid;
+
+const VERSION: &str = "cd5e4e1a-3f58-4b44-b4d4-bba3a2b70a32";
+
+/// The document that the changes are made to.
+const DOCUMENT: AuditedEntity = AuditedEntity {
+    entity_type: "document",
+    entity_id: "1",
+};
+
+/// The principal that is allowed to browse the audit history.
+const AUDITOR: &str = "auditor";
+
+/// The header that the principal making each request is taken from.
+const PRINCIPAL_HEADER: &str = "x-principal";
+
+/// Create a test suite in which the auditor is allowed to browse the audit history.
+async fn test_suite(fixtures: &[&str]) -> TestSuite {
+    TestSuite::with_settings(fixtures, |settings| {
+        settings.principal_header = Some(HeaderName::from_static(PRINCIPAL_HEADER));
+        settings.audit_principals = vec![AUDITOR.to_owned()];
+    })
+    .await
+}
+
+/// Build a request made by the given principal.
+fn request(uri: &str, principal: &str) -> Request {
+    TestRequest::get()
+        .uri(uri)
+        .header(PRINCIPAL_HEADER, principal)
+        .to_request()
+}
+
+#[actix_rt::test]
+pub async fn record_and_browse_history() {
+    let test_suite = test_suite(&["documents.sql"]).await;
+    let context = AuditContext {
+        principal: Some("graham".to_owned()),
+        trace_id: Some("4bf92f3577b34da6a3ce929d0e0e4736".to_owned()),
+    };
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+    let row = tx
+        .update_versioned_audited(
+            &context,
+            DOCUMENT,
+            "documents",
+            &1,
+            Uuid::parse_str(VERSION).unwrap(),
+            &[("title", &"First")],
+        )
+        .await
+        .expect("Failed to update document");
+    tx.update_versioned_audited(
+        &AuditContext::default(),
+        DOCUMENT,
+        "documents",
+        &1,
+        row.get("version"),
+        &[("title", &"Second")],
+    )
+    .await
+    .expect("Failed to update document");
+    tx.commit().await.expect("Failed to commit transaction");
+
+    let response = test_suite
+        .inject(request("/audit/document/1", AUDITOR))
+        .await;
+
+    check!(response.status == 200);
+    check!(response.headers.get("content-type").unwrap() == "application/hal+json");
+
+    let body = response.to_json().unwrap();
+    check!(body["count"] == 2);
+    check!(body["_links"]["self"]["href"] == "http://localhost:8080/audit/document/1");
+
+    let links = response
+        .headers
+        .get_all("link")
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+    check!(
+        links
+            == vec![
+                r#"<http://localhost:8080/audit/document/1>; rel="first""#,
+                r#"<http://localhost:8080/audit/document/1>; rel="self""#,
+            ]
+    );
+
+    let entries = body["_embedded"]["item"].as_array().unwrap();
+    check!(entries.len() == 2);
+
+    check!(entries[0]["action"] == "updated");
+    check!(entries[0]["before"]["title"] == "First");
+    check!(entries[0]["after"]["title"] == "Second");
+    check!(entries[0]["principal"].is_null());
+    check!(entries[0]["traceId"].is_null());
+
+    check!(entries[1]["action"] == "updated");
+    check!(entries[1]["before"]["title"] == "Original");
+    check!(entries[1]["before"]["version"] == VERSION);
+    check!(entries[1]["after"]["title"] == "First");
+    check!(entries[1]["principal"] == "graham");
+    check!(entries[1]["traceId"] == "4bf92f3577b34da6a3ce929d0e0e4736");
+}
+
+#[actix_rt::test]
+pub async fn edits_are_audited() {
+    let test_suite = test_suite(&["documents.sql"]).await;
+
+    let response = test_suite
+        .inject(
+            TestRequest::put()
+                .uri("/documents/1")
+                .header(PRINCIPAL_HEADER, "graham")
+                .header("if-match", format!(r#""{VERSION}""#))
+                .set_json(&json!({ "title": "Updated" }))
+                .to_request(),
+        )
+        .await;
+    check!(response.status == 200);
+
+    // A rejected edit changes nothing, so isn't recorded.
+    let response = test_suite
+        .inject(
+            TestRequest::put()
+                .uri("/documents/1")
+                .header(PRINCIPAL_HEADER, "graham")
+                .header("if-match", format!(r#""{VERSION}""#))
+                .set_json(&json!({ "title": "Stale" }))
+                .to_request(),
+        )
+        .await;
+    check!(response.status == 412);
+
+    let response = test_suite
+        .inject(request("/audit/document/1", AUDITOR))
+        .await;
+
+    check!(response.status == 200);
+    let body = response.to_json().unwrap();
+    let_assert!(Some(entries) = body["_embedded"]["item"].as_array());
+    check!(entries.len() == 1);
+    check!(entries[0]["action"] == "updated");
+    check!(entries[0]["before"]["title"] == "Original");
+    check!(entries[0]["after"]["title"] == "Updated");
+    check!(entries[0]["principal"] == "graham");
+}
+
+#[actix_rt::test]
+pub async fn browse_unknown_entity() {
+    let test_suite = test_suite(&[]).await;
+
+    let response = test_suite
+        .inject(request("/audit/document/1", AUDITOR))
+        .await;
+
+    check!(response.status == 200);
+    check!(response.to_json().unwrap()["_embedded"]["item"] == serde_json::json!([]));
+}
+
+#[actix_rt::test]
+pub async fn browse_invalid_cursor() {
+    let test_suite = test_suite(&[]).await;
+
+    let response = test_suite
+        .inject(request("/audit/document/1?cursor=invalid!", AUDITOR))
+        .await;
+
+    check!(response.status == 400);
+    check!(response.headers.get("content-type").unwrap() == "application/problem+json");
+
+    let body = response.to_json().unwrap();
+    check!(body["type"] == "tag:grahamcox.co.uk,2021:bigbang/problems/invalid-cursor");
+    check!(body["title"] == "Invalid cursor");
+    check!(body["status"] == 400);
+    check!(body["detail"] == "Cursor was not correctly encoded");
+}
+
+#[actix_rt::test]
+pub async fn browse_anonymously() {
+    let test_suite = test_suite(&[]).await;
+
+    let response = test_suite
+        .inject(TestRequest::get().uri("/audit/document/1").to_request())
+        .await;
+
+    check!(response.status == 403);
+    check!(response.headers.get("content-type").unwrap() == "application/problem+json");
+}
+
+#[actix_rt::test]
+pub async fn browse_without_permission() {
+    let test_suite = test_suite(&[]).await;
+
+    let response = test_suite
+        .inject(request("/audit/document/1", "graham"))
+        .await;
+
+    check!(response.status == 403);
+    check!(response.headers.get("content-type").unwrap() == "application/problem+json");
+}
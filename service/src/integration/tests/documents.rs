@@ -0,0 +1,238 @@
+use crate::integration::TestSuite;
+use actix_web::test::TestRequest;
+use assert2::{check, let_assert};
+use serde_json::json;
+
+const VERSION: &str = "cd5e4e1a-3f58-4b44-b4d4-bba3a2b70a32";
+
+#[actix_rt::test]
+pub async fn get_document() {
+    let test_suite = TestSuite::with_fixtures(&["documents.sql"]).await;
+
+    let response = test_suite
+        .inject(TestRequest::get().uri("/documents/1").to_request())
+        .await;
+
+    check!(response.status == 200);
+    check!(response.headers.get("etag").unwrap() == &format!(r#""{VERSION}""#));
+    check!(response.headers.contains_key("last-modified"));
+    check!(
+        response.to_json().unwrap()
+            == json!({
+                "id": 1,
+                "title": "Original",
+                "_links": {
+                    "self": { "href": "http://localhost:8080/documents/1" }
+                }
+            })
+    );
+}
+
+#[actix_rt::test]
+pub async fn get_document_as_hal_forms() {
+    let test_suite = TestSuite::with_fixtures(&["documents.sql"]).await;
+
+    let response = test_suite
+        .inject(
+            TestRequest::get()
+                .uri("/documents/1")
+                .header("accept", "application/prs.hal-forms+json")
+                .to_request(),
+        )
+        .await;
+
+    check!(response.status == 200);
+    check!(response.headers.get("content-type").unwrap() == "application/prs.hal-forms+json");
+    check!(
+        response.to_json().unwrap()["_templates"]
+            == json!({
+                "default": {
+                    "title": "Edit document",
+                    "method": "PUT",
+                    "contentType": "application/json",
+                    "properties": [
+                        {
+                            "name": "title",
+                            "type": "text",
+                            "prompt": "Title",
+                            "required": true,
+                            "value": "Original"
+                        }
+                    ]
+                }
+            })
+    );
+}
+
+#[actix_rt::test]
+pub async fn get_unknown_document() {
+    let test_suite = TestSuite::new().await;
+
+    let response = test_suite
+        .inject(TestRequest::get().uri("/documents/1").to_request())
+        .await;
+
+    check!(response.status == 404);
+    check!(response.headers.get("content-type").unwrap() == "application/problem+json");
+}
+
+#[actix_rt::test]
+pub async fn update_with_current_etag() {
+    let test_suite = TestSuite::with_fixtures(&["documents.sql"]).await;
+
+    let response = test_suite
+        .inject(TestRequest::get().uri("/documents/1").to_request())
+        .await;
+    let_assert!(Some(etag) = response.headers.get("etag"));
+
+    let updated = test_suite
+        .inject(
+            TestRequest::put()
+                .uri("/documents/1")
+                .header("if-match", etag.clone())
+                .set_json(&json!({ "title": "Updated" }))
+                .to_request(),
+        )
+        .await;
+
+    check!(updated.status == 200);
+    check!(updated.to_json().unwrap()["title"] == "Updated");
+    let_assert!(Some(new_etag) = updated.headers.get("etag"));
+    check!(new_etag != etag);
+
+    // The old version is now stale, so the same edit is rejected.
+    let stale = test_suite
+        .inject(
+            TestRequest::put()
+                .uri("/documents/1")
+                .header("if-match", etag.clone())
+                .set_json(&json!({ "title": "Stale" }))
+                .to_request(),
+        )
+        .await;
+
+    check!(stale.status == 412);
+    check!(stale.headers.get("content-type").unwrap() == "application/problem+json");
+    let body = stale.to_json().unwrap();
+    check!(body["type"] == "tag:grahamcox.co.uk,2021:bigbang/problems/version-conflict");
+    check!(body["status"] == 412);
+
+    let current = test_suite
+        .inject(TestRequest::get().uri("/documents/1").to_request())
+        .await;
+    check!(current.headers.get("etag") == Some(new_etag));
+    check!(current.to_json().unwrap()["title"] == "Updated");
+}
+
+#[actix_rt::test]
+pub async fn update_without_if_match() {
+    let test_suite = TestSuite::with_fixtures(&["documents.sql"]).await;
+
+    let response = test_suite
+        .inject(
+            TestRequest::put()
+                .uri("/documents/1")
+                .set_json(&json!({ "title": "Updated" }))
+                .to_request(),
+        )
+        .await;
+
+    check!(response.status == 200);
+    check!(response.headers.get("etag").unwrap() != &format!(r#""{VERSION}""#));
+    check!(response.to_json().unwrap()["title"] == "Updated");
+}
+
+#[actix_rt::test]
+pub async fn update_unknown_document() {
+    let test_suite = TestSuite::new().await;
+
+    let response = test_suite
+        .inject(
+            TestRequest::put()
+                .uri("/documents/1")
+                .header("if-match", format!(r#""{VERSION}""#))
+                .set_json(&json!({ "title": "Updated" }))
+                .to_request(),
+        )
+        .await;
+
+    check!(response.status == 404);
+}
+
+#[actix_rt::test]
+pub async fn list_documents() {
+    let test_suite = TestSuite::with_fixtures(&["documents.sql", "more-documents.sql"]).await;
+
+    let response = test_suite
+        .inject(TestRequest::get().uri("/documents?limit=2").to_request())
+        .await;
+
+    check!(response.status == 200);
+    check!(
+        response.to_json().unwrap()
+            == json!({
+                "page": 1,
+                "total": 3,
+                "count": 2,
+                "_links": {
+                    "first": { "href": "http://localhost:8080/documents?offset=0&limit=2" },
+                    "last": { "href": "http://localhost:8080/documents?offset=2&limit=2" },
+                    "next": { "href": "http://localhost:8080/documents?offset=2&limit=2" },
+                    "self": { "href": "http://localhost:8080/documents?offset=0&limit=2" }
+                },
+                "_embedded": {
+                    "item": [
+                        {
+                            "id": 1,
+                            "title": "Original",
+                            "_links": {
+                                "self": { "href": "http://localhost:8080/documents/1" }
+                            }
+                        },
+                        {
+                            "id": 2,
+                            "title": "Second",
+                            "_links": {
+                                "self": { "href": "http://localhost:8080/documents/2" }
+                            }
+                        }
+                    ]
+                }
+            })
+    );
+}
+
+#[actix_rt::test]
+pub async fn list_last_page() {
+    let test_suite = TestSuite::with_fixtures(&["documents.sql", "more-documents.sql"]).await;
+
+    let response = test_suite
+        .inject(
+            TestRequest::get()
+                .uri("/documents?offset=2&limit=2")
+                .to_request(),
+        )
+        .await;
+
+    check!(response.status == 200);
+    let body = response.to_json().unwrap();
+    check!(body["page"] == 2);
+    check!(body["count"] == 1);
+    check!(body["_embedded"]["item"][0]["title"] == "Third");
+    check!(body["_links"]["prev"]["href"] == "http://localhost:8080/documents?offset=0&limit=2");
+    check!(body["_links"].get("next") == None);
+}
+
+#[actix_rt::test]
+pub async fn list_limit_is_capped() {
+    let test_suite = TestSuite::with_fixtures(&["documents.sql"]).await;
+
+    let response = test_suite
+        .inject(TestRequest::get().uri("/documents?limit=1000").to_request())
+        .await;
+
+    check!(response.status == 200);
+    let body = response.to_json().unwrap();
+    check!(body["total"] == 1);
+    check!(body["_links"]["self"]["href"] == "http://localhost:8080/documents?offset=0&limit=100");
+}
@@ -0,0 +1,36 @@
+use assert2::check;
+
+#[actix_rt::test]
+pub async fn load_fixtures() {
+    let test_suite =
+        crate::integration::TestSuite::with_fixtures(&["people.sql", "people.yaml", "people.json"])
+            .await;
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+    let people = tx
+        .query(
+            "SELECT name, age, created = '2021-01-01T00:00:00Z' AS fixed_created FROM people ORDER BY name",
+            &[],
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| {
+            (
+                row.get::<_, String>("name"),
+                row.get::<_, Option<i32>>("age"),
+                row.get::<_, bool>("fixed_created"),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    check!(
+        people
+            == vec![
+                ("Barney".to_owned(), Some(38), false),
+                ("Fred".to_owned(), None, false),
+                ("Graham".to_owned(), Some(40), true),
+            ]
+    );
+}
@@ -14,16 +14,104 @@ pub async fn get_home() {
 
     check!(response.headers.get("content-type").unwrap() == "application/hal+json");
     check!(response.headers.get("cache-control").unwrap() == "public, max-age=3600");
+    let vary = response
+        .headers
+        .get_all("vary")
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+    check!(vary == vec!["Accept", "host"]);
 
-    assert_json_snapshot!(response.to_json().unwrap(), @r###"
+    assert_json_snapshot!(response.to_json().unwrap(), @r#"
     {
       "name": "bigbang",
       "version": "0.1.0",
       "_links": {
         "self": {
-          "href": "/"
+          "href": "http://localhost:8080/"
         }
       }
     }
-    "###);
+    "#);
+}
+
+#[actix_rt::test]
+pub async fn get_home_behind_proxy() {
+    let test_suite = crate::integration::TestSuite::with_settings(&[], |settings| {
+        settings.trust_forwarded_headers = true;
+    })
+    .await;
+
+    let response = test_suite
+        .inject(
+            TestRequest::get()
+                .uri("/")
+                .header("x-forwarded-proto", "https")
+                .header("x-forwarded-host", "api.example.com")
+                .header("x-forwarded-prefix", "/bigbang")
+                .to_request(),
+        )
+        .await;
+
+    check!(response.status == 200);
+
+    let vary = response
+        .headers
+        .get_all("vary")
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+    check!(
+        vary == vec![
+            "Accept",
+            "host, forwarded, x-forwarded-proto, x-forwarded-host, x-forwarded-prefix"
+        ]
+    );
+
+    let body = response.to_json().unwrap();
+    check!(body["_links"]["self"]["href"] == "https://api.example.com/bigbang/");
+}
+
+#[actix_rt::test]
+pub async fn get_home_with_untrusted_forwarded_headers() {
+    let test_suite = crate::integration::TestSuite::new().await;
+
+    let response = test_suite
+        .inject(
+            TestRequest::get()
+                .uri("/")
+                .header("x-forwarded-proto", "https")
+                .header("x-forwarded-host", "evil.example")
+                .to_request(),
+        )
+        .await;
+
+    check!(response.status == 200);
+
+    let body = response.to_json().unwrap();
+    check!(body["_links"]["self"]["href"] == "http://localhost:8080/");
+}
+
+#[actix_rt::test]
+pub async fn get_home_not_modified() {
+    let test_suite = crate::integration::TestSuite::new().await;
+
+    let response = test_suite
+        .inject(TestRequest::get().uri("/").to_request())
+        .await;
+
+    check!(response.status == 200);
+    let etag = response.headers.get("etag").unwrap().clone();
+
+    let response = test_suite
+        .inject(
+            TestRequest::get()
+                .uri("/")
+                .header("if-none-match", etag.clone())
+                .to_request(),
+        )
+        .await;
+
+    check!(response.status == 304);
+    check!(response.headers.get("etag").unwrap() == etag);
+    check!(response.headers.get("cache-control").unwrap() == "public, max-age=3600");
+    check!(response.body.is_empty());
 }
@@ -0,0 +1,159 @@
+use crate::database::migrate::{dry_run, error_position, lock::MIGRATIONS_LOCK_KEY, LockError};
+use crate::integration::database::TestDatabase;
+use assert2::{check, let_assert};
+use prometheus::Registry;
+use std::time::{Duration, Instant};
+
+#[actix_rt::test]
+pub async fn dry_run_up_to_date() {
+    let test_suite = crate::integration::TestSuite::new().await;
+
+    let result = dry_run(test_suite.db(), Duration::from_mins(1))
+        .await
+        .expect("Failed to acquire migrations lock");
+
+    check!(result.pending.is_empty());
+    check!(result.outcome.is_ok());
+}
+
+#[actix_rt::test]
+pub async fn dry_run_is_rolled_back() {
+    let test_suite = crate::integration::TestSuite::new().await;
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+    tx.batch_execute(
+        "DROP TABLE audit_log;
+        DELETE FROM __migrations WHERE migration_file = '20210301-000000-audit.sql';",
+    )
+    .await
+    .expect("Failed to un-apply migration");
+    tx.commit().await.expect("Failed to commit transaction");
+
+    let result = dry_run(test_suite.db(), Duration::from_mins(1))
+        .await
+        .expect("Failed to acquire migrations lock");
+
+    check!(result.pending.len() == 1);
+    check!(result.pending[0].file == "20210301-000000-audit.sql");
+    check!(result.pending[0].sql.starts_with("CREATE TABLE audit_log("));
+    check!(result.outcome.is_ok());
+
+    let tx = conn.begin().await;
+    let rows = tx
+        .query(
+            "SELECT
+              to_regclass('audit_log')::TEXT AS audit_log,
+              (SELECT COUNT(*) FROM __migrations) AS migrations",
+            &[],
+        )
+        .await
+        .expect("Failed to query database");
+    tx.commit().await.expect("Failed to commit transaction");
+
+    check!(rows[0].get::<_, Option<String>>("audit_log") == None);
+    check!(rows[0].get::<_, i64>("migrations") == 2);
+}
+
+#[actix_rt::test]
+pub async fn failure_position() {
+    let test_suite = crate::integration::TestSuite::new().await;
+    let sql = "SELECT 1;\nSELECT oops FROM nowhere;";
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+    let_assert!(Err(error) = tx.batch_execute(sql).await);
+    tx.rollback()
+        .await
+        .expect("Failed to roll back transaction");
+
+    check!(error_position(sql, &error) == Some((2, 18)));
+}
+
+#[actix_rt::test]
+pub async fn lock_timeout() {
+    let database = TestDatabase::new().await;
+    let db = crate::database::component::Builder::new(&database.url)
+        .with_migrate(false)
+        .build(&Registry::new())
+        .await
+        .expect("Failed to build database component")
+        .database;
+
+    let mut conn = db.connect().await;
+    let tx = conn.begin().await;
+    tx.query("SELECT pg_advisory_xact_lock($1)", &[&MIGRATIONS_LOCK_KEY])
+        .await
+        .expect("Failed to take migrations lock");
+
+    let started = Instant::now();
+    let result = crate::database::component::Builder::new(&database.url)
+        .with_migration_lock_timeout(Duration::from_millis(500))
+        .build(&Registry::new())
+        .await;
+
+    check!(started.elapsed() >= Duration::from_millis(500));
+    let_assert!(Err(LockError::Timeout { timeout, holders }) = result);
+    check!(timeout == Duration::from_millis(500));
+    check!(holders.len() == 1);
+    check!(holders[0].query.as_deref() == Some("SELECT pg_advisory_xact_lock($1)"));
+
+    tx.rollback()
+        .await
+        .expect("Failed to roll back transaction");
+
+    let result = crate::database::component::Builder::new(&database.url)
+        .with_migration_lock_timeout(Duration::from_millis(500))
+        .build(&Registry::new())
+        .await;
+    check!(result.is_ok());
+}
+
+#[actix_rt::test]
+pub async fn migrate_whilst_waiting_for_lock() {
+    let database = TestDatabase::new().await;
+    let db = crate::database::component::Builder::new(&database.url)
+        .with_migrate(false)
+        .build(&Registry::new())
+        .await
+        .expect("Failed to build database component")
+        .database;
+
+    // Start from an empty database, so that there are migrations to apply.
+    let mut conn = db.connect().await;
+    let tx = conn.begin().await;
+    tx.batch_execute("DROP SCHEMA public CASCADE; CREATE SCHEMA public;")
+        .await
+        .expect("Failed to empty database");
+    tx.commit().await.expect("Failed to commit transaction");
+
+    // Hold the lock until both instances are waiting for it, so that whichever gets it second has
+    // to see the migrations applied by the first.
+    let tx = conn.begin().await;
+    tx.query("SELECT pg_advisory_xact_lock($1)", &[&MIGRATIONS_LOCK_KEY])
+        .await
+        .expect("Failed to take migrations lock");
+
+    let (first_prometheus, second_prometheus) = (Registry::new(), Registry::new());
+    let first = crate::database::component::Builder::new(&database.url).build(&first_prometheus);
+    let second = crate::database::component::Builder::new(&database.url).build(&second_prometheus);
+    let release = async {
+        actix_rt::time::delay_for(Duration::from_millis(500)).await;
+        tx.rollback()
+            .await
+            .expect("Failed to roll back transaction");
+    };
+
+    let (first, second, ()) = futures::join!(first, second, release);
+    check!(first.is_ok());
+    check!(second.is_ok());
+
+    let tx = conn.begin().await;
+    let rows = tx
+        .query("SELECT COUNT(*) AS migrations FROM __migrations", &[])
+        .await
+        .expect("Failed to query database");
+    tx.commit().await.expect("Failed to commit transaction");
+
+    check!(rows[0].get::<_, i64>("migrations") == 3);
+}
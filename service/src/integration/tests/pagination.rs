@@ -0,0 +1,52 @@
+use crate::database::pagination::{Cursor, KeysetQuery, Order, Page};
+use crate::database::Transaction;
+use assert2::{check, let_assert};
+
+const QUERY: KeysetQuery = KeysetQuery {
+    sql: "SELECT id, name FROM items WHERE id <= $1",
+    sort_column: "name",
+    id_column: "id",
+    order: Order::Ascending,
+    limit: 4,
+};
+
+async fn query_page(
+    tx: &Transaction<'_>,
+    cursor: Option<&Cursor<String, i32>>,
+) -> Page<String, i32> {
+    tx.query_page(&QUERY, cursor, &[&9])
+        .await
+        .expect("Failed to query page")
+}
+
+fn ids(page: &Page<String, i32>) -> Vec<i32> {
+    page.rows.iter().map(|row| row.get("id")).collect()
+}
+
+#[actix_rt::test]
+pub async fn walk_pages() {
+    let test_suite = crate::integration::TestSuite::with_fixtures(&["items.sql"]).await;
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+
+    // Sorted by name then id, the items are: 3, 6, 9, 1, 4, 7, 2, 5, 8
+    let first = query_page(&tx, None).await;
+    check!(ids(&first) == vec![3, 6, 9, 1]);
+    check!(first.prev.is_none());
+    let_assert!(Some(next) = &first.next);
+
+    let second = query_page(&tx, Some(next)).await;
+    check!(ids(&second) == vec![4, 7, 2, 5]);
+    let_assert!(Some(prev) = &second.prev);
+    let_assert!(Some(next) = &second.next);
+
+    let third = query_page(&tx, Some(next)).await;
+    check!(ids(&third) == vec![8]);
+    check!(third.next.is_none());
+
+    let back = query_page(&tx, Some(prev)).await;
+    check!(ids(&back) == vec![3, 6, 9, 1]);
+    check!(back.prev.is_none());
+    check!(back.next.is_some());
+}
@@ -0,0 +1,43 @@
+use insta::assert_snapshot;
+
+/// Snapshot of the database schema after all migrations have been applied.
+/// If this fails then the schema has changed, and the snapshot should be updated once the change
+/// has been confirmed as intended.
+#[actix_rt::test]
+pub async fn schema() {
+    let test_suite = crate::integration::TestSuite::new().await;
+
+    let schema = crate::integration::dump_schema(test_suite.db()).await;
+
+    assert_snapshot!(schema, @r"
+    TABLE public.__migrations
+      COLUMN migration_file text NOT NULL
+      COLUMN sequence integer NOT NULL DEFAULT nextval('__migrations_sequence_seq'::regclass)
+      COLUMN executed timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP
+      COLUMN executed_from text NOT NULL DEFAULT inet_client_addr()
+      CONSTRAINT __migrations_pkey PRIMARY KEY (migration_file)
+      INDEX CREATE UNIQUE INDEX __migrations_pkey ON public.__migrations USING btree (migration_file)
+    TABLE public.audit_log
+      COLUMN audit_id bigint NOT NULL DEFAULT nextval('audit_log_audit_id_seq'::regclass)
+      COLUMN entity_type text NOT NULL
+      COLUMN entity_id text NOT NULL
+      COLUMN action text NOT NULL
+      COLUMN before jsonb
+      COLUMN after jsonb
+      COLUMN principal text
+      COLUMN trace_id text
+      COLUMN recorded timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP
+      CONSTRAINT audit_log_pkey PRIMARY KEY (audit_id)
+      INDEX CREATE INDEX audit_log_entity_idx ON public.audit_log USING btree (entity_type, entity_id, audit_id)
+      INDEX CREATE UNIQUE INDEX audit_log_pkey ON public.audit_log USING btree (audit_id)
+    TABLE public.documents
+      COLUMN id integer NOT NULL
+      COLUMN version uuid NOT NULL
+      COLUMN title text NOT NULL
+      COLUMN modified timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP
+      CONSTRAINT documents_pkey PRIMARY KEY (id)
+      INDEX CREATE UNIQUE INDEX documents_pkey ON public.documents USING btree (id)
+    SEQUENCE public.__migrations_sequence_seq integer START 1 INCREMENT 1
+    SEQUENCE public.audit_log_audit_id_seq bigint START 1 INCREMENT 1
+    ");
+}
@@ -0,0 +1,49 @@
+use crate::integration::database::TestDatabase;
+use assert2::check;
+use prometheus::Registry;
+
+async fn current_query(sql_comments: bool) -> String {
+    let database = TestDatabase::new().await;
+    let db = crate::database::component::Builder::new(&database.url)
+        .with_sql_comments(sql_comments)
+        .build(&Registry::new())
+        .await
+        .expect("Failed to build database component")
+        .database;
+
+    let mut conn = db.connect().await;
+    let tx = conn.begin().await;
+    let rows = tx
+        .query("SELECT current_query()", &[])
+        .await
+        .expect("Failed to query database");
+    tx.commit().await.expect("Failed to commit transaction");
+
+    rows[0].get(0)
+}
+
+#[actix_rt::test]
+pub async fn comments_disabled() {
+    let query =
+        crate::server::with_route("/audit/{entity_type}/{entity_id}", current_query(false)).await;
+
+    check!(query == "SELECT current_query()");
+}
+
+#[actix_rt::test]
+pub async fn comments_enabled() {
+    let query =
+        crate::server::with_route("/audit/{entity_type}/{entity_id}", current_query(true)).await;
+
+    check!(
+        query
+            == "SELECT current_query() /*route='%2Faudit%2F%7Bentity_type%7D%2F%7Bentity_id%7D'*/"
+    );
+}
+
+#[actix_rt::test]
+pub async fn comments_enabled_outside_request() {
+    let query = current_query(true).await;
+
+    check!(query == "SELECT current_query()");
+}
@@ -0,0 +1,90 @@
+use crate::database::versioned::UpdateError;
+use assert2::{check, let_assert};
+use uuid::Uuid;
+
+const VERSION: &str = "cd5e4e1a-3f58-4b44-b4d4-bba3a2b70a32";
+
+#[actix_rt::test]
+pub async fn update_current_version() {
+    let test_suite = crate::integration::TestSuite::with_fixtures(&["documents.sql"]).await;
+    let version = Uuid::parse_str(VERSION).unwrap();
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+
+    let_assert!(
+        Ok(row) = tx
+            .update_versioned("documents", &1, version, &[("title", &"Updated")])
+            .await
+    );
+    check!(row.get::<_, &str>("title") == "Updated");
+    check!(row.get::<_, Uuid>("version") != version);
+}
+
+#[actix_rt::test]
+pub async fn update_stale_version() {
+    let test_suite = crate::integration::TestSuite::with_fixtures(&["documents.sql"]).await;
+    let version = Uuid::parse_str(VERSION).unwrap();
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+
+    let_assert!(
+        Err(UpdateError::Conflict { current }) = tx
+            .update_versioned("documents", &1, Uuid::new_v4(), &[("title", &"Updated")])
+            .await
+    );
+    check!(current == version);
+
+    let rows = tx
+        .query("SELECT title FROM documents WHERE id = 1", &[])
+        .await
+        .unwrap();
+    check!(rows[0].get::<_, &str>("title") == "Original");
+}
+
+#[actix_rt::test]
+pub async fn update_unknown_resource() {
+    let test_suite = crate::integration::TestSuite::with_fixtures(&["documents.sql"]).await;
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+
+    let_assert!(
+        Err(UpdateError::NotFound) = tx
+            .update_versioned("documents", &2, Uuid::new_v4(), &[("title", &"Updated")])
+            .await
+    );
+}
+
+#[actix_rt::test]
+pub async fn update_schema_qualified_table() {
+    let test_suite = crate::integration::TestSuite::with_fixtures(&["documents.sql"]).await;
+
+    let mut conn = test_suite.db().connect().await;
+    let tx = conn.begin().await;
+
+    let_assert!(
+        Ok(row) = tx
+            .update_versioned(
+                "public.documents",
+                &1,
+                Uuid::parse_str(VERSION).unwrap(),
+                &[("title", &"Updated")]
+            )
+            .await
+    );
+    check!(row.get::<_, &str>("title") == "Updated");
+
+    let_assert!(
+        Err(UpdateError::Conflict { current }) = tx
+            .update_versioned(
+                "public.documents",
+                &1,
+                Uuid::new_v4(),
+                &[("title", &"Again")]
+            )
+            .await
+    );
+    check!(current == row.get::<_, Uuid>("version"));
+}
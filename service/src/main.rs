@@ -1,7 +1,9 @@
 #![deny(clippy::all, clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
+mod audit;
 mod database;
+mod documents;
 mod home;
 mod http;
 #[cfg(test)]
@@ -9,9 +11,11 @@ mod integration;
 mod server;
 mod service;
 
+use actix_http::http::header::HeaderName;
 use config::{Config, Environment};
 use dotenv::dotenv;
 use serde::Deserialize;
+use std::{path::PathBuf, time::Duration};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::Registry;
 
@@ -22,6 +26,27 @@ struct Settings {
     pub port: u16,
     /// The connection URL for the database
     pub database_url: String,
+    /// The namespace to register the database metrics under
+    pub database_metrics_namespace: String,
+    /// Whether to also register the database metrics under their deprecated names
+    pub legacy_database_metrics: bool,
+    /// Whether to append sqlcommenter comments describing the request and trace to SQL statements
+    pub database_sql_comments: bool,
+    /// How long to wait, in seconds, for another instance that is already migrating the database
+    pub database_migration_lock_timeout: u64,
+    /// The public base URL that the service is reachable at, if this can't be worked out from
+    /// the requests
+    pub public_base_url: Option<String>,
+    /// Whether the service is only reachable through a proxy, so that the `Forwarded` and
+    /// `X-Forwarded-*` headers it sets can be trusted
+    pub trust_forwarded_headers: bool,
+    /// The request header that the authenticating proxy in front of the service puts the
+    /// principal making each request into, if there is one
+    pub principal_header: Option<String>,
+    /// Comma-separated list of the principals that are allowed to browse the audit history
+    pub audit_principals: Option<String>,
+    /// How long to wait, in milliseconds, for each contributor of links to the home document
+    pub home_link_timeout: u64,
 }
 
 impl Default for Settings {
@@ -33,6 +58,18 @@ impl Default for Settings {
         let mut s = Config::new();
         s.set_default("port", 8000)
             .expect("Failed to set default value for 'port'");
+        s.set_default("database_metrics_namespace", "bigbang")
+            .expect("Failed to set default value for 'database_metrics_namespace'");
+        s.set_default("legacy_database_metrics", true)
+            .expect("Failed to set default value for 'legacy_database_metrics'");
+        s.set_default("database_sql_comments", false)
+            .expect("Failed to set default value for 'database_sql_comments'");
+        s.set_default("database_migration_lock_timeout", 60)
+            .expect("Failed to set default value for 'database_migration_lock_timeout'");
+        s.set_default("trust_forwarded_headers", false)
+            .expect("Failed to set default value for 'trust_forwarded_headers'");
+        s.set_default("home_link_timeout", 1000)
+            .expect("Failed to set default value for 'home_link_timeout'");
 
         s.merge(Environment::default())
             .expect("Failed to load environment properties");
@@ -46,6 +83,27 @@ impl From<Settings> for service::Settings {
         Self {
             port: settings.port,
             database_url: settings.database_url,
+            database_metrics_namespace: settings.database_metrics_namespace,
+            legacy_database_metrics: settings.legacy_database_metrics,
+            database_sql_comments: settings.database_sql_comments,
+            database_migration_lock_timeout: Duration::from_secs(
+                settings.database_migration_lock_timeout,
+            ),
+            public_base_url: settings.public_base_url,
+            trust_forwarded_headers: settings.trust_forwarded_headers,
+            principal_header: settings.principal_header.map(|header| {
+                HeaderName::from_bytes(header.as_bytes())
+                    .expect("Failed to parse 'principal_header' as a header name")
+            }),
+            audit_principals: settings
+                .audit_principals
+                .iter()
+                .flat_map(|principals| principals.split(','))
+                .map(str::trim)
+                .filter(|principal| !principal.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+            home_link_timeout: Duration::from_millis(settings.home_link_timeout),
         }
     }
 }
@@ -69,6 +127,96 @@ async fn main() {
     let settings = Settings::default();
     tracing::debug!(settings = ?settings, "Loaded settings");
 
-    let service = service::Service::new(settings.into()).await;
-    service.start().await;
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None | Some("serve") => match service::Service::new(settings.into()).await {
+            Ok(service) => service.start().await,
+            Err(e) => {
+                eprintln!("Failed to start service: {e}");
+                std::process::exit(1);
+            }
+        },
+        Some("migrate") => migrate(&settings, args.any(|arg| arg == "--dry-run")).await,
+        Some("seed") => seed(&settings, &args.map(PathBuf::from).collect::<Vec<_>>()).await,
+        Some(command) => {
+            eprintln!("Unknown command: {command}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Migrate the database schema to the latest version.
+///
+/// # Parameters
+/// - `settings` - The application settings
+/// - `dry_run` - Whether to only report what the migration would do, without changing anything
+async fn migrate(settings: &Settings, dry_run: bool) {
+    let lock_timeout = Duration::from_secs(settings.database_migration_lock_timeout);
+    let built = database(settings)
+        .with_migrate(!dry_run)
+        .build(&prometheus::Registry::new())
+        .await;
+    let result = match built {
+        Ok(db) if dry_run => database::migrate::dry_run(&db.database, lock_timeout).await,
+        Ok(_) => return,
+        Err(e) => Err(e),
+    };
+    let result = result.unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+
+    for migration in &result.pending {
+        println!("-- {}\n{}\n", migration.file, migration.sql.trim_end());
+    }
+
+    match result.outcome {
+        Ok(()) => println!(
+            "Dry run succeeded. {} migrations would be applied",
+            result.pending.len()
+        ),
+        Err(e) => {
+            eprintln!("Dry run failed. {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Load the given fixture files into the database, to seed it with data for local development.
+///
+/// # Parameters
+/// - `settings` - The application settings
+/// - `fixtures` - The paths to the fixture files to load
+async fn seed(settings: &Settings, fixtures: &[PathBuf]) {
+    if fixtures.is_empty() {
+        eprintln!("Usage: bigbang seed <fixture>...");
+        std::process::exit(1);
+    }
+
+    let db = database(settings)
+        .build(&prometheus::Registry::new())
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        });
+
+    if let Err(e) = database::fixtures::load(&db.database, fixtures).await {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// Create the builder for the database component, configured from the application settings.
+///
+/// # Parameters
+/// - `settings` - The application settings
+fn database(settings: &Settings) -> database::component::Builder {
+    database::component::Builder::new(&settings.database_url)
+        .with_metrics_namespace(&settings.database_metrics_namespace)
+        .with_legacy_metrics(settings.legacy_database_metrics)
+        .with_sql_comments(settings.database_sql_comments)
+        .with_migration_lock_timeout(Duration::from_secs(
+            settings.database_migration_lock_timeout,
+        ))
 }
@@ -1,8 +1,13 @@
 pub(super) mod component;
 mod span;
 
+#[cfg(test)]
+pub use span::with_route;
+pub use span::{current_route, TraceId};
+
 use std::sync::Arc;
 
+use crate::http::routes::Routes;
 use actix_cors::Cors;
 use actix_http::http::header;
 use actix_web::{middleware::Logger, web::ServiceConfig, App, HttpServer};
@@ -16,6 +21,12 @@ pub trait Configurer: Send + Sync {
     /// # Parameters
     /// - `config` - The Actix `ServiceConfig` that routes and data can be wired onto.
     fn configure_server(&self, config: &mut ServiceConfig);
+
+    /// Register the routes that this component handles.
+    ///
+    /// # Parameters
+    /// - `routes` - The registry to add the routes to
+    fn register_routes(&self, _routes: &mut Routes) {}
 }
 
 /// Wrapper around the HTTP Server.
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use super::{Configurer, Server};
+use crate::http::routes::Routes;
 use prometheus::Registry;
 
 /// Component representing the HTTP Server
@@ -20,9 +21,75 @@ impl Builder {
         self
     }
 
+    /// Build the HTTP Server.
+    /// The routes that every component handles are collected together, and links are only ever
+    /// generated to routes from this set.
+    ///
+    /// # Panics
+    /// If any route is invalid, or two components register different routes with the same name
     pub fn build(self, prometheus: Registry, port: u16) -> Component {
+        let mut config = self.config;
+
+        let mut routes = Routes::default();
+        for c in &config {
+            c.register_routes(&mut routes);
+        }
+        config.push(Arc::new(routes));
+
         Component {
-            server: Server::new(port, self.config, prometheus),
+            server: Server::new(port, config, prometheus),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::routes::Route;
+    use actix_web::web::ServiceConfig;
+    use assert2::check;
+
+    struct TestComponent {
+        name: &'static str,
+        path: &'static str,
+    }
+
+    impl Configurer for TestComponent {
+        fn configure_server(&self, _: &mut ServiceConfig) {}
+
+        fn register_routes(&self, routes: &mut Routes) {
+            routes.register(Route::new(self.name, self.path));
         }
     }
+
+    #[test]
+    fn shares_routes() {
+        let component = Builder::default()
+            .with_component(Arc::new(TestComponent {
+                name: "first",
+                path: "/first",
+            }))
+            .with_component(Arc::new(TestComponent {
+                name: "second",
+                path: "/second",
+            }))
+            .build(Registry::new(), 0);
+
+        check!(component.server.config.len() == 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Route first is already registered for /first")]
+    fn conflicting_routes() {
+        Builder::default()
+            .with_component(Arc::new(TestComponent {
+                name: "first",
+                path: "/first",
+            }))
+            .with_component(Arc::new(TestComponent {
+                name: "first",
+                path: "/other",
+            }))
+            .build(Registry::new(), 0);
+    }
 }
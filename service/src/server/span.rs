@@ -2,9 +2,58 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use actix_service::{Service, Transform};
-use actix_web::{dev::ServiceRequest, dev::ServiceResponse, Error};
+use actix_web::{dev::ServiceRequest, dev::ServiceResponse, Error, HttpMessage};
 use futures::future::{ok, Ready};
 use futures::Future;
+use opentelemetry::trace::TraceContextExt;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+tokio::task_local! {
+    /// The route pattern that the request currently being processed matched.
+    static ROUTE: Option<String>;
+}
+
+/// Get the route pattern that the request currently being processed matched.
+///
+/// # Returns
+/// The route pattern, or `None` if not processing a request or the request matched no route
+pub fn current_route() -> Option<String> {
+    ROUTE.try_with(Clone::clone).ok().flatten()
+}
+
+/// Run a future as if it were processing a request that matched the given route pattern.
+#[cfg(test)]
+pub async fn with_route<F>(route: &str, f: F) -> F::Output
+where
+    F: Future,
+{
+    ROUTE.scope(Some(route.to_owned()), f).await
+}
+
+/// The ID of the trace that a request is being processed as part of.
+/// This is stored in the request extensions by the `Span` middleware, if the request is being traced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceId(pub String);
+
+impl TraceId {
+    /// Get the ID of the trace that the given span is part of.
+    ///
+    /// # Parameters
+    /// - `span` - The span to get the trace ID of
+    ///
+    /// # Returns
+    /// The trace ID, or `None` if the span isn't being exported as part of a trace
+    pub fn of(span: &tracing::Span) -> Option<Self> {
+        let context = span.context();
+        let span_context = context.span().span_context();
+
+        if span_context.is_valid() {
+            Some(Self(span_context.trace_id().to_hex()))
+        } else {
+            None
+        }
+    }
+}
 
 /// Middleware for applying a tracing `Span` around the entire HTTP request, and tracking certain
 /// details on it.
@@ -57,9 +106,14 @@ where
             http.status_code = tracing::field::Empty
         );
 
+        if let Some(trace_id) = TraceId::of(&span) {
+            req.extensions_mut().insert(trace_id);
+        }
+
+        let route = req.match_pattern();
         let fut = self.service.call(req);
 
-        Box::pin(async move {
+        Box::pin(ROUTE.scope(route, async move {
             let span = span;
             let _enter = span.enter();
 
@@ -68,6 +122,6 @@ where
             span.record("http.status_code", &response.status().as_u16());
 
             Ok(response)
-        })
+        }))
     }
 }
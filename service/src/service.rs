@@ -1,41 +1,87 @@
 #[cfg(test)]
 pub mod testing;
 
+use crate::database::migrate::LockError;
 use crate::server::Server;
+use actix_http::http::header::HeaderName;
 use prometheus::Registry;
+use std::{sync::Arc, time::Duration};
 
 /// The actual service
 pub struct Service {
     server: Server,
+    #[cfg(test)]
+    database: Arc<crate::database::Database>,
 }
 
 #[derive(Debug)]
 pub struct Settings {
     pub port: u16,
     pub database_url: String,
+    pub database_metrics_namespace: String,
+    pub legacy_database_metrics: bool,
+    pub database_sql_comments: bool,
+    pub database_migration_lock_timeout: Duration,
+    pub public_base_url: Option<String>,
+    pub trust_forwarded_headers: bool,
+    pub principal_header: Option<HeaderName>,
+    pub audit_principals: Vec<String>,
+    pub home_link_timeout: Duration,
 }
 
 impl Service {
     /// Create a new instance of the service
+    ///
+    /// # Errors
+    /// If the database schema couldn't be migrated because another instance held the migrations
+    /// lock for too long
     #[tracing::instrument(name = "Service::new")]
-    pub async fn new(settings: Settings) -> Self {
+    pub async fn new(settings: Settings) -> Result<Self, LockError> {
         tracing::debug!("Building Big Bang");
 
         let prometheus = Registry::new();
-        let _db =
-            crate::database::component::Component::new(&settings.database_url, &prometheus).await;
+        let db = crate::database::component::Builder::new(&settings.database_url)
+            .with_metrics_namespace(&settings.database_metrics_namespace)
+            .with_legacy_metrics(settings.legacy_database_metrics)
+            .with_sql_comments(settings.database_sql_comments)
+            .with_migration_lock_timeout(settings.database_migration_lock_timeout)
+            .build(&prometheus)
+            .await?;
 
-        let home = crate::home::component::Builder::default().build();
+        let audit = crate::audit::component::Builder::new(db.database.clone())
+            .with_auditors(settings.audit_principals)
+            .build();
+        let documents = crate::documents::component::Builder::new(db.database.clone()).build();
+        let home = crate::home::component::Builder::default()
+            .with_timeout(settings.home_link_timeout)
+            .build(&prometheus);
 
-        let server = crate::server::component::Builder::default()
-            .with_component(home)
-            .build(prometheus, settings.port);
+        let mut server = crate::server::component::Builder::default()
+            .with_component(Arc::new(crate::http::problem::ExtractorProblems))
+            .with_component(Arc::new(crate::http::negotiation::Renderers::default()))
+            .with_component(audit)
+            .with_component(documents)
+            .with_component(home);
+        if let Some(public_base_url) = settings.public_base_url {
+            server =
+                server.with_component(Arc::new(crate::http::urls::PublicBaseUrl(public_base_url)));
+        }
+        if settings.trust_forwarded_headers {
+            server = server.with_component(Arc::new(crate::http::urls::TrustForwardedHeaders));
+        }
+        if let Some(principal_header) = settings.principal_header {
+            server =
+                server.with_component(Arc::new(crate::http::PrincipalHeader(principal_header)));
+        }
+        let server = server.build(prometheus, settings.port);
 
         tracing::debug!("Built Big Bang");
 
-        Self {
+        Ok(Self {
             server: server.server,
-        }
+            #[cfg(test)]
+            database: db.database,
+        })
     }
 
     /// Start the service processing requests
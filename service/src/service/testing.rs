@@ -1,9 +1,16 @@
 use actix_http::Request;
 use actix_web::App;
+use std::sync::Arc;
 
 use super::Service;
+use crate::database::Database;
 
 impl Service {
+    /// Get the database that the service is using. Only used for testing.
+    pub fn database(&self) -> &Arc<Database> {
+        &self.database
+    }
+
     /// Inject a request into the server. Only used for testing.
     ///
     /// # Parameters
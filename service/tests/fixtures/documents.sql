@@ -0,0 +1 @@
+INSERT INTO documents(id, version, title) VALUES (1, 'cd5e4e1a-3f58-4b44-b4d4-bba3a2b70a32', 'Original');
@@ -0,0 +1,6 @@
+CREATE TABLE items(
+  id INTEGER PRIMARY KEY,
+  name TEXT NOT NULL
+);
+
+INSERT INTO items(id, name) SELECT i, 'Item ' || (i % 3) FROM generate_series(1, 10) AS i;
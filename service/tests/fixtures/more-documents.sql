@@ -0,0 +1,2 @@
+INSERT INTO documents(id, version, title) VALUES (2, '6e1f9a0c-5d42-4d2f-9c8b-3c1e4a6f7b21', 'Second');
+INSERT INTO documents(id, version, title) VALUES (3, 'a3b7c9d1-2e4f-4a6b-8c0d-1e2f3a4b5c6d', 'Third');
@@ -0,0 +1,5 @@
+CREATE TABLE people(
+  name TEXT PRIMARY KEY,
+  age INTEGER,
+  created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);